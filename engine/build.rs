@@ -1,4 +1,6 @@
 use image::{GenericImage, RgbaImage};
+use std::collections::HashSet;
+use std::env;
 use std::fs;
 use std::path::Path;
 
@@ -45,7 +47,79 @@ fn main() {
 
     atlas.save(&dest_path).expect("Failed to save atlas");
 
+    let atlas_textures: HashSet<&str> = texture_matrix
+        .iter()
+        .flatten()
+        .map(|name| name.as_str())
+        .collect();
+    bundle_block_definitions(&atlas_textures);
+
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=assets/textures/block/atlas.json");
     println!("cargo:rerun-if-changed=assets/textures/block/");
+    println!("cargo:rerun-if-changed=assets/blocks/");
+}
+
+// Collects every `assets/blocks/*.json` definition into a single array in OUT_DIR,
+// which the engine embeds with `include_str!`. Definitions are validated here so a
+// typo in a texture name or a duplicated id fails the build instead of the game.
+fn bundle_block_definitions(atlas_textures: &HashSet<&str>) {
+    let blocks_dir = Path::new("assets/blocks");
+    let mut definition_paths: Vec<_> = fs::read_dir(blocks_dir)
+        .expect("Failed to read assets/blocks")
+        .map(|entry| entry.expect("Failed to read block definition entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    definition_paths.sort();
+
+    let mut seen_ids = HashSet::new();
+    let mut seen_names = HashSet::new();
+    let mut definitions = Vec::new();
+
+    for path in definition_paths {
+        let content = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+        let definition: serde_json::Value = serde_json::from_str(&content)
+            .unwrap_or_else(|e| panic!("Failed to parse {}: {}", path.display(), e));
+
+        let id = definition["id"]
+            .as_u64()
+            .unwrap_or_else(|| panic!("{} is missing a numeric \"id\"", path.display()));
+        let name = definition["name"]
+            .as_str()
+            .unwrap_or_else(|| panic!("{} is missing a \"name\"", path.display()));
+
+        if id > u16::MAX as u64 {
+            panic!("{}: block id {} does not fit in a u16", path.display(), id);
+        }
+        if !seen_ids.insert(id) {
+            panic!("{}: duplicate block id {}", path.display(), id);
+        }
+        if !seen_names.insert(name.to_string()) {
+            panic!("{}: duplicate block name \"{}\"", path.display(), name);
+        }
+
+        if let Some(textures) = definition["textures"].as_object() {
+            for (face, texture) in textures {
+                let texture = texture.as_str().unwrap_or_else(|| {
+                    panic!("{}: texture for \"{}\" must be a string", path.display(), face)
+                });
+                if !atlas_textures.contains(texture) {
+                    panic!(
+                        "{}: texture \"{}\" for face \"{}\" is not listed in atlas.json",
+                        path.display(),
+                        texture,
+                        face
+                    );
+                }
+            }
+        }
+
+        definitions.push(definition);
+    }
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is not set");
+    let bundled = serde_json::to_string(&definitions).expect("Failed to serialize blocks");
+    fs::write(Path::new(&out_dir).join("blocks.json"), bundled)
+        .expect("Failed to write bundled block definitions");
 }
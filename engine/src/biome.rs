@@ -0,0 +1,89 @@
+use crate::block::BlockType;
+use crate::block_registry::TintKind;
+
+// Climate zone of a single block column. Chosen by world generation and stored per column
+// in the chunk so the mesher can colour grass and leaves.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Plains = 0,
+    Forest = 1,
+    Desert = 2,
+    Taiga = 3,
+}
+
+// Multiplied with the (greyscale) texture colour of untinted faces: leaves it unchanged.
+const NO_TINT: [f32; 3] = [1.0, 1.0, 1.0];
+
+impl Biome {
+    pub fn from_id(id: u8) -> Option<Biome> {
+        match id {
+            0 => Some(Biome::Plains),
+            1 => Some(Biome::Forest),
+            2 => Some(Biome::Desert),
+            3 => Some(Biome::Taiga),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Plains => "Plains",
+            Biome::Forest => "Forest",
+            Biome::Desert => "Desert",
+            Biome::Taiga => "Taiga",
+        }
+    }
+
+    // Top block of a column and the few layers below it.
+    pub fn surface_block(&self) -> BlockType {
+        match self {
+            Biome::Desert => BlockType::SAND,
+            _ => BlockType::GRASS,
+        }
+    }
+
+    pub fn subsurface_block(&self) -> BlockType {
+        match self {
+            Biome::Desert => BlockType::SAND,
+            _ => BlockType::DIRT,
+        }
+    }
+
+    // Chance for each grass column to grow a tree.
+    pub fn tree_chance(&self) -> f64 {
+        match self {
+            Biome::Plains => 0.01,
+            Biome::Forest => 0.06,
+            Biome::Desert => 0.0,
+            Biome::Taiga => 0.03,
+        }
+    }
+
+    pub fn grass_tint(&self) -> [f32; 3] {
+        match self {
+            Biome::Plains => [0.4, 0.9, 0.35],
+            Biome::Forest => [0.3, 0.75, 0.25],
+            Biome::Desert => [0.75, 0.72, 0.35],
+            Biome::Taiga => [0.45, 0.7, 0.55],
+        }
+    }
+
+    pub fn foliage_tint(&self) -> [f32; 3] {
+        match self {
+            Biome::Plains => [0.25, 0.55, 0.15],
+            Biome::Forest => [0.18, 0.45, 0.1],
+            Biome::Desert => [0.5, 0.55, 0.2],
+            Biome::Taiga => [0.25, 0.45, 0.35],
+        }
+    }
+
+    // Per-vertex colour for a face with the given tint kind in this biome.
+    pub fn tint_color(&self, tint: Option<TintKind>) -> [f32; 3] {
+        match tint {
+            Some(TintKind::Grass) => self.grass_tint(),
+            Some(TintKind::Foliage) => self.foliage_tint(),
+            None => NO_TINT,
+        }
+    }
+}
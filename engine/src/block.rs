@@ -1,20 +1,41 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum BlockType {
-    Air, // Optional, for empty spaces
-    Dirt,
-    Grass,
-    Bedrock,
-    OakLog,
-    OakLeaves,
-    // Add more block types here later if needed
+use crate::block_registry::{BlockDefinition, TintKind, registry};
+
+// A compact id into the block registry (see assets/blocks/*.json).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockType(pub u16);
+
+impl BlockType {
+    // Blocks the engine refers to directly. Everything else is looked up by name.
+    pub const AIR: BlockType = BlockType(0);
+    pub const DIRT: BlockType = BlockType(1);
+    pub const GRASS: BlockType = BlockType(2);
+    pub const BEDROCK: BlockType = BlockType(3);
+    pub const OAK_LOG: BlockType = BlockType(4);
+    pub const OAK_LEAVES: BlockType = BlockType(5);
+    pub const STONE: BlockType = BlockType(6);
+    pub const SAND: BlockType = BlockType(9);
+    pub const WATER: BlockType = BlockType(13);
+
+    pub fn definition(&self) -> &'static BlockDefinition {
+        registry().get(*self)
+    }
+
+    pub fn name(&self) -> &'static str {
+        &self.definition().name
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+// Flowing fluid runs from level 1 next to its source to MAX_FLUID_LEVEL at the far end of
+// the flow, one level per block. Sources, and every block that is not a fluid, are level 0.
+pub const MAX_FLUID_LEVEL: u8 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Block {
     pub block_type: BlockType,
     pub tree_id: Option<u32>, // Added to identify trees
     pub sky_light: u8,
     pub block_light: u8,
+    pub fluid_level: u8,
     // We can add more properties later, like light levels, custom data, etc.
 }
 
@@ -25,6 +46,14 @@ impl Block {
             tree_id: None,
             sky_light: 0,
             block_light: 0,
+            fluid_level: 0,
+        }
+    }
+
+    pub fn new_fluid(block_type: BlockType, fluid_level: u8) -> Self {
+        Block {
+            fluid_level,
+            ..Block::new(block_type)
         }
     }
 
@@ -35,52 +64,44 @@ impl Block {
             tree_id: Some(tree_id),
             sky_light: 0,
             block_light: 0,
+            fluid_level: 0,
         }
     }
 
     pub fn is_solid(&self) -> bool {
-        match self.block_type {
-            BlockType::Air => false,
-            BlockType::OakLeaves => false, // Leaves are not solid for physics
-            _ => true,                     // All other current types are solid
-        }
+        self.block_type.definition().solid
     }
 
     pub fn is_transparent(&self) -> bool {
-        match self.block_type {
-            BlockType::OakLeaves => true,
-            BlockType::Air => true, // Air is also visually transparent
-            _ => false,
-        }
+        self.block_type.definition().transparent
+    }
+
+    pub fn is_fluid(&self) -> bool {
+        self.block_type.definition().fluid
+    }
+
+    pub fn is_fluid_source(&self) -> bool {
+        self.is_fluid() && self.fluid_level == 0
     }
 
-    // Later, we can add methods here to get texture coordinates
-    // based on BlockType and potentially block face.
-    // For now, we'll keep it simple.
+    // Height of a fluid's surface within its block, lower the further it has flowed. Only
+    // meaningful for fluids; fluid with more of itself above fills the whole block instead.
+    pub fn fluid_height(&self) -> f32 {
+        (MAX_FLUID_LEVEL + 1 - self.fluid_level) as f32 / (MAX_FLUID_LEVEL + 2) as f32
+    }
+
+    // Block light the block gives off by itself, 0..=15.
+    pub fn light_emission(&self) -> u8 {
+        self.block_type.definition().light_emission
+    }
 
     // Returns atlas indices (column, row) for each face: [Front, Back, Right, Left, Top, Bottom]
     pub fn get_texture_atlas_indices(&self) -> [[f32; 2]; 6] {
-        match self.block_type {
-            BlockType::Dirt => [[2.0, 0.0]; 6], // dirt.png
-            BlockType::Grass => [
-                [1.0, 0.0], // Front (grass_block_side.png)
-                [1.0, 0.0], // Back (grass_block_side.png)
-                [1.0, 0.0], // Right (grass_block_side.png)
-                [1.0, 0.0], // Left (grass_block_side.png)
-                [0.0, 0.0], // Top (grass_block_top.png)
-                [2.0, 0.0], // Bottom (dirt.png)
-            ],
-            BlockType::Bedrock => [[3.0, 0.0]; 6], // bedrock.png
-            BlockType::Air => [[15.0, 15.0]; 6],   // Default/error texture (far corner of atlas)
-            BlockType::OakLog => [
-                [4.0, 0.0], // Side (oak_log.png)
-                [4.0, 0.0], // Side (oak_log.png)
-                [4.0, 0.0], // Side (oak_log.png)
-                [4.0, 0.0], // Side (oak_log.png)
-                [5.0, 0.0], // Top (oak_log_top.png)
-                [5.0, 0.0], // Bottom (oak_log_top.png)
-            ],
-            BlockType::OakLeaves => [[6.0, 0.0]; 6], // oak_leaves.png
-        }
+        self.block_type.definition().texture_atlas_indices
+    }
+
+    // Tint for each face, same order as `get_texture_atlas_indices`.
+    pub fn get_face_tints(&self) -> [Option<TintKind>; 6] {
+        self.block_type.definition().face_tints
     }
 }
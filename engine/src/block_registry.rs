@@ -0,0 +1,284 @@
+use crate::block::BlockType;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// Bundled by build.rs from assets/blocks/*.json (already validated against the atlas).
+const BLOCK_DEFINITIONS_JSON: &str = include_str!(concat!(env!("OUT_DIR"), "/blocks.json"));
+const ATLAS_JSON: &str = include_str!("../assets/textures/block/atlas.json");
+
+// Atlas cell used when a face has no texture (e.g. air). Far corner of the atlas.
+const MISSING_TEXTURE_INDEX: [f32; 2] = [15.0, 15.0];
+
+// Blocks the engine itself refers to (world generation, tests, UI). Their ids are fixed
+// so the `BlockType` constants stay valid; the registry checks the names on load.
+const BUILTIN_BLOCKS: [(BlockType, &str); 9] = [
+    (BlockType::AIR, "air"),
+    (BlockType::DIRT, "dirt"),
+    (BlockType::GRASS, "grass"),
+    (BlockType::BEDROCK, "bedrock"),
+    (BlockType::OAK_LOG, "oak_log"),
+    (BlockType::OAK_LEAVES, "oak_leaves"),
+    (BlockType::STONE, "stone"),
+    (BlockType::SAND, "sand"),
+    (BlockType::WATER, "water"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TintKind {
+    Grass,
+    Foliage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaceName {
+    Front,
+    Back,
+    Right,
+    Left,
+    Top,
+    Bottom,
+}
+
+impl FaceName {
+    // Index into the per-face arrays: [Front, Back, Right, Left, Top, Bottom]
+    fn index(self) -> usize {
+        match self {
+            FaceName::Front => 0,
+            FaceName::Back => 1,
+            FaceName::Right => 2,
+            FaceName::Left => 3,
+            FaceName::Top => 4,
+            FaceName::Bottom => 5,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TextureNames {
+    all: Option<String>,
+    side: Option<String>,
+    top: Option<String>,
+    bottom: Option<String>,
+    front: Option<String>,
+    back: Option<String>,
+    right: Option<String>,
+    left: Option<String>,
+}
+
+impl TextureNames {
+    // The most specific entry wins: front/back/right/left > side > all, top/bottom > all.
+    fn resolve(&self) -> [Option<&str>; 6] {
+        let all = self.all.as_deref();
+        let side = self.side.as_deref().or(all);
+        [
+            self.front.as_deref().or(side),
+            self.back.as_deref().or(side),
+            self.right.as_deref().or(side),
+            self.left.as_deref().or(side),
+            self.top.as_deref().or(all),
+            self.bottom.as_deref().or(all),
+        ]
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TintDefinition {
+    kind: TintKind,
+    // Faces that receive the tint; all faces when omitted.
+    faces: Option<Vec<FaceName>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBlockDefinition {
+    id: u16,
+    name: String,
+    #[serde(default = "default_true")]
+    solid: bool,
+    #[serde(default)]
+    transparent: bool,
+    #[serde(default)]
+    fluid: bool,
+    #[serde(default)]
+    textures: TextureNames,
+    tint: Option<TintDefinition>,
+    #[serde(default)]
+    light_emission: u8,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockDefinition {
+    pub id: BlockType,
+    pub name: String,
+    pub solid: bool,
+    pub transparent: bool,
+    // Flows into neighbouring air (see `World::tick_fluids`) and is meshed as a fluid surface.
+    pub fluid: bool,
+    // Atlas indices (column, row) for each face: [Front, Back, Right, Left, Top, Bottom]
+    pub texture_atlas_indices: [[f32; 2]; 6],
+    // Tint applied per face, same order as `texture_atlas_indices`.
+    pub face_tints: [Option<TintKind>; 6],
+    pub light_emission: u8,
+}
+
+pub struct BlockRegistry {
+    definitions: Vec<Option<BlockDefinition>>,
+    ids_by_name: HashMap<String, BlockType>,
+}
+
+lazy_static::lazy_static! {
+    static ref BLOCK_REGISTRY: BlockRegistry = BlockRegistry::from_json(BLOCK_DEFINITIONS_JSON, ATLAS_JSON)
+        .unwrap_or_else(|e| panic!("Invalid block definitions: {}", e));
+}
+
+pub fn registry() -> &'static BlockRegistry {
+    &BLOCK_REGISTRY
+}
+
+impl BlockRegistry {
+    pub fn from_json(definitions_json: &str, atlas_json: &str) -> Result<Self, String> {
+        let raw_definitions: Vec<RawBlockDefinition> = serde_json::from_str(definitions_json)
+            .map_err(|e| format!("Failed to parse block definitions: {}", e))?;
+        let atlas_rows: Vec<Vec<String>> = serde_json::from_str(atlas_json)
+            .map_err(|e| format!("Failed to parse atlas.json: {}", e))?;
+
+        let mut atlas_indices: HashMap<&str, [f32; 2]> = HashMap::new();
+        for (row_idx, row) in atlas_rows.iter().enumerate() {
+            for (col_idx, texture_name) in row.iter().enumerate() {
+                atlas_indices.insert(texture_name, [col_idx as f32, row_idx as f32]);
+            }
+        }
+
+        let mut registry = BlockRegistry {
+            definitions: Vec::new(),
+            ids_by_name: HashMap::new(),
+        };
+
+        for raw in raw_definitions {
+            let mut texture_atlas_indices = [MISSING_TEXTURE_INDEX; 6];
+            for (face_idx, texture_name) in raw.textures.resolve().iter().enumerate() {
+                if let Some(texture_name) = texture_name {
+                    texture_atlas_indices[face_idx] =
+                        *atlas_indices.get(texture_name).ok_or_else(|| {
+                            format!(
+                                "Block '{}' uses texture '{}' which is not in the atlas",
+                                raw.name, texture_name
+                            )
+                        })?;
+                }
+            }
+
+            let mut face_tints = [None; 6];
+            if let Some(tint) = &raw.tint {
+                match &tint.faces {
+                    Some(faces) => {
+                        for face in faces {
+                            face_tints[face.index()] = Some(tint.kind);
+                        }
+                    }
+                    None => face_tints = [Some(tint.kind); 6],
+                }
+            }
+
+            let id = BlockType(raw.id);
+            let slot = raw.id as usize;
+            if registry.definitions.len() <= slot {
+                registry.definitions.resize(slot + 1, None);
+            }
+            if registry.definitions[slot].is_some() {
+                return Err(format!("Duplicate block id {}", raw.id));
+            }
+            if registry.ids_by_name.insert(raw.name.clone(), id).is_some() {
+                return Err(format!("Duplicate block name '{}'", raw.name));
+            }
+            registry.definitions[slot] = Some(BlockDefinition {
+                id,
+                name: raw.name,
+                solid: raw.solid,
+                transparent: raw.transparent,
+                fluid: raw.fluid,
+                texture_atlas_indices,
+                face_tints,
+                light_emission: raw.light_emission.min(15),
+            });
+        }
+
+        for (block_type, expected_name) in BUILTIN_BLOCKS {
+            match registry.try_get(block_type) {
+                Some(definition) if definition.name == expected_name => {}
+                _ => {
+                    return Err(format!(
+                        "Built-in block '{}' must be defined with id {}",
+                        expected_name, block_type.0
+                    ));
+                }
+            }
+        }
+
+        Ok(registry)
+    }
+
+    pub fn try_get(&self, block_type: BlockType) -> Option<&BlockDefinition> {
+        self.definitions
+            .get(block_type.0 as usize)
+            .and_then(|definition| definition.as_ref())
+    }
+
+    // Unknown ids fall back to air so corrupted data can never crash the renderer.
+    pub fn get(&self, block_type: BlockType) -> &BlockDefinition {
+        self.try_get(block_type)
+            .or_else(|| self.try_get(BlockType::AIR))
+            .expect("Air is always registered")
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<BlockType> {
+        self.ids_by_name.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_definitions_load() {
+        let registry = registry();
+        for (block_type, name) in BUILTIN_BLOCKS {
+            assert_eq!(registry.by_name(name), Some(block_type));
+        }
+        assert!(!registry.get(BlockType::AIR).solid);
+        assert!(registry.get(BlockType::AIR).transparent);
+        assert!(!registry.get(BlockType::OAK_LEAVES).solid);
+    }
+
+    #[test]
+    fn test_face_texture_resolution_and_tints() {
+        let grass = registry().get(BlockType::GRASS);
+        let dirt = registry().get(BlockType::DIRT);
+        // Bottom of grass shares the dirt texture, sides differ from the top.
+        assert_eq!(
+            grass.texture_atlas_indices[5],
+            dirt.texture_atlas_indices[0]
+        );
+        assert_ne!(
+            grass.texture_atlas_indices[0],
+            grass.texture_atlas_indices[4]
+        );
+        assert_eq!(grass.face_tints[4], Some(TintKind::Grass));
+        assert_eq!(grass.face_tints[0], None);
+    }
+
+    #[test]
+    fn test_unknown_texture_is_rejected() {
+        let definitions = r#"[{ "id": 0, "name": "air", "textures": { "all": "nope.png" } }]"#;
+        assert!(BlockRegistry::from_json(definitions, "[[\"dirt.png\"]]").is_err());
+    }
+}
@@ -1,4 +1,5 @@
-use glam::Mat4;
+use crate::physics::AABB;
+use glam::{Mat4, Vec3, Vec4};
 
 // We'll also define our Uniform struct here for now.
 // It needs to be `repr(C)` to ensure predictable memory layout for the shader.
@@ -29,3 +30,70 @@ impl CameraUniform {
     // The old update_view_proj that took a &Camera is no longer relevant
     // as the Camera struct itself is being removed from active use in State.
 }
+
+// The six planes of a view frustum, taken from a view-projection matrix (Gribb/Hartmann) for
+// wgpu's 0..1 depth range. Each plane is (normal, distance) with the normal pointing inwards.
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    pub fn from_view_proj(view_proj: Mat4) -> Self {
+        let [row_x, row_y, row_z, row_w] = [0, 1, 2, 3].map(|index| view_proj.row(index));
+        Frustum {
+            planes: [
+                row_w + row_x, // left
+                row_w - row_x, // right
+                row_w + row_y, // bottom
+                row_w - row_y, // top
+                row_z,         // near
+                row_w - row_z, // far
+            ],
+        }
+    }
+
+    // False only if the box lies entirely behind one of the planes. A box just outside a
+    // corner of the frustum can still pass, which costs a draw call but never a hole.
+    pub fn intersects_aabb(&self, aabb: &AABB) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            // The corner of the box furthest along the plane's normal.
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frustum_looking_down_negative_z() -> Frustum {
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let projection = Mat4::perspective_rh(45.0f32.to_radians(), 1.0, 0.1, 100.0);
+        Frustum::from_view_proj(projection * view)
+    }
+
+    fn unit_box_at(center: Vec3) -> AABB {
+        AABB::new(center - Vec3::splat(0.5), center + Vec3::splat(0.5))
+    }
+
+    #[test]
+    fn test_frustum_keeps_boxes_in_view() {
+        let frustum = frustum_looking_down_negative_z();
+        assert!(frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, -10.0))));
+        // Straddling the left edge of the view.
+        assert!(frustum.intersects_aabb(&unit_box_at(Vec3::new(-4.3, 0.0, -10.0))));
+        // Around the camera itself.
+        assert!(frustum.intersects_aabb(&unit_box_at(Vec3::ZERO)));
+    }
+
+    #[test]
+    fn test_frustum_culls_boxes_out_of_view() {
+        let frustum = frustum_looking_down_negative_z();
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, 10.0))));
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(20.0, 0.0, -10.0))));
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, -20.0, -10.0))));
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, -200.0))));
+    }
+}
@@ -1,179 +1,388 @@
+use crate::biome::Biome;
 use crate::block::{Block, BlockType};
-use rand::Rng; // Assuming block.rs is in the same directory
+use crate::chunk_storage::{NibbleArray, PalettedStorage};
+use crate::save::codec::{ByteReader, ByteWriter, invalid_data};
 use std::collections::VecDeque;
+use std::io;
 
 pub const CHUNK_WIDTH: usize = 16;
-pub const CHUNK_HEIGHT: usize = 32;
 pub const CHUNK_DEPTH: usize = 16;
 
+// Chunks are columns of SECTION_SIZE³ sections stacked from MIN_Y upwards. The world's
+// vertical extent is MIN_Y..MAX_Y; MIN_Y may be negative, and CHUNK_HEIGHT must be a
+// multiple of SECTION_SIZE.
+pub const SECTION_SIZE: usize = 16;
+pub const MIN_Y: i32 = -64;
+pub const CHUNK_HEIGHT: usize = 320;
+pub const MAX_Y: i32 = MIN_Y + CHUNK_HEIGHT as i32;
+pub const SECTION_COUNT: usize = CHUNK_HEIGHT / SECTION_SIZE;
+
+const _: () = assert!(CHUNK_HEIGHT.is_multiple_of(SECTION_SIZE));
+const _: () = assert!(CHUNK_WIDTH == SECTION_SIZE && CHUNK_DEPTH == SECTION_SIZE);
+
+// What an empty (unstored) section is made of: air with a clear view of the sky.
+const OPEN_AIR: Block = Block {
+    block_type: BlockType::AIR,
+    tree_id: None,
+    sky_light: 15,
+    block_light: 0,
+    fluid_level: 0,
+};
+
+// `Chunk` methods take chunk-local y, counted from MIN_Y. These convert to and from world y.
+pub fn world_y_to_local(world_y: i32) -> Option<usize> {
+    (MIN_Y..MAX_Y)
+        .contains(&world_y)
+        .then(|| (world_y - MIN_Y) as usize)
+}
+
+pub fn local_y_to_world(local_y: usize) -> i32 {
+    local_y as i32 + MIN_Y
+}
+
+// Bumped whenever `Chunk::encode` changes its layout. Version 1 had no fluid levels and is
+// still read.
+const CHUNK_FORMAT_VERSION: u8 = 2;
+
+const SECTION_VOLUME: usize = SECTION_SIZE * SECTION_SIZE * SECTION_SIZE;
+
+// Everything about a block except its light; the palette entries of a section.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct BlockState {
+    block_type: BlockType,
+    tree_id: Option<u32>,
+    fluid_level: u8,
+}
+
+impl BlockState {
+    fn is_transparent(&self) -> bool {
+        self.block_type.definition().transparent
+    }
+}
+
+const AIR_STATE: BlockState = BlockState {
+    block_type: BlockType::AIR,
+    tree_id: None,
+    fluid_level: 0,
+};
+
+#[derive(Clone, PartialEq)]
+struct Section {
+    // All three are indexed `(x * SECTION_SIZE + y) * SECTION_SIZE + z`, with y local to the
+    // section.
+    blocks: PalettedStorage<BlockState>,
+    sky_light: NibbleArray,
+    block_light: NibbleArray,
+}
+
+impl Section {
+    fn open_air() -> Self {
+        Section {
+            blocks: PalettedStorage::new(SECTION_VOLUME, AIR_STATE),
+            sky_light: NibbleArray::filled(SECTION_VOLUME, OPEN_AIR.sky_light),
+            block_light: NibbleArray::filled(SECTION_VOLUME, OPEN_AIR.block_light),
+        }
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        (x * SECTION_SIZE + y % SECTION_SIZE) * SECTION_SIZE + z
+    }
+
+    fn get(&self, index: usize) -> Block {
+        let state = self.blocks.get(index);
+        Block {
+            block_type: state.block_type,
+            tree_id: state.tree_id,
+            sky_light: self.sky_light.get(index),
+            block_light: self.block_light.get(index),
+            fluid_level: state.fluid_level,
+        }
+    }
+
+    fn set(&mut self, index: usize, block: Block) {
+        self.blocks.set(
+            index,
+            BlockState {
+                block_type: block.block_type,
+                tree_id: block.tree_id,
+                fluid_level: block.fluid_level,
+            },
+        );
+        self.sky_light.set(index, block.sky_light);
+        self.block_light.set(index, block.block_light);
+    }
+
+    fn is_open_air(&self) -> bool {
+        let only_air = match self.blocks.palette() {
+            [state] => *state == AIR_STATE,
+            _ => (0..SECTION_VOLUME).all(|index| self.blocks.get(index) == AIR_STATE),
+        };
+        only_air
+            && self.sky_light.is_filled_with(OPEN_AIR.sky_light)
+            && self.block_light.is_filled_with(OPEN_AIR.block_light)
+    }
+
+    fn write_to(&self, writer: &mut ByteWriter) {
+        self.blocks.write_to(writer, |writer, state| {
+            writer.put_u16(state.block_type.0);
+            // Tree ids start at 1, so 0 can stand for "no tree".
+            writer.put_u32(state.tree_id.unwrap_or(0));
+            writer.put_u8(state.fluid_level);
+        });
+        self.sky_light.write_to(writer);
+        self.block_light.write_to(writer);
+    }
+
+    fn read_from(reader: &mut ByteReader, version: u8) -> io::Result<Self> {
+        let blocks = PalettedStorage::read_from(reader, SECTION_VOLUME, |reader| {
+            Ok(BlockState {
+                block_type: BlockType(reader.get_u16()?),
+                tree_id: Some(reader.get_u32()?).filter(|&tree_id| tree_id != 0),
+                fluid_level: if version >= 2 { reader.get_u8()? } else { 0 },
+            })
+        })?;
+        Ok(Section {
+            blocks,
+            sky_light: NibbleArray::read_from(reader, SECTION_VOLUME)?,
+            block_light: NibbleArray::read_from(reader, SECTION_VOLUME)?,
+        })
+    }
+
+    fn memory_usage(&self) -> ChunkMemoryUsage {
+        ChunkMemoryUsage {
+            sections: 1,
+            block_bytes: std::mem::size_of::<Section>() + self.blocks.heap_bytes(),
+            light_bytes: self.sky_light.heap_bytes() + self.block_light.heap_bytes(),
+        }
+    }
+}
+
+// Heap memory held by the blocks of one or more chunks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkMemoryUsage {
+    pub sections: usize,
+    pub block_bytes: usize,
+    pub light_bytes: usize,
+}
+
+impl ChunkMemoryUsage {
+    pub fn total_bytes(&self) -> usize {
+        self.block_bytes + self.light_bytes
+    }
+}
+
+impl std::ops::AddAssign for ChunkMemoryUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.sections += other.sections;
+        self.block_bytes += other.block_bytes;
+        self.light_bytes += other.light_bytes;
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub struct Chunk {
     pub coord: (i32, i32),
-    blocks: Vec<Vec<Vec<Block>>>,
+    // Bottom to top. Sections holding nothing but sky-lit air are not stored.
+    sections: Vec<Option<Box<Section>>>,
+    // Biome of each column, indexed `x * CHUNK_DEPTH + z`.
+    biomes: Vec<Biome>,
 }
 
 impl Chunk {
     pub fn new(coord_x: i32, coord_z: i32) -> Self {
-        let blocks =
-            vec![vec![vec![Block::new(BlockType::Air); CHUNK_DEPTH]; CHUNK_HEIGHT]; CHUNK_WIDTH];
         Chunk {
             coord: (coord_x, coord_z),
-            blocks,
+            sections: vec![None; SECTION_COUNT],
+            biomes: vec![Biome::Plains; CHUNK_WIDTH * CHUNK_DEPTH],
         }
     }
 
-    pub fn generate_terrain(&mut self) {
-        let surface_level = CHUNK_HEIGHT / 2;
-
-        for x in 0..CHUNK_WIDTH {
-            for z in 0..CHUNK_DEPTH {
-                self.blocks[x][0][z] = Block::new(BlockType::Bedrock);
-                for y in 1..CHUNK_HEIGHT {
-                    if y < surface_level {
-                        self.blocks[x][y][z] = Block::new(BlockType::Dirt);
-                    } else if y == surface_level {
-                        self.blocks[x][y][z] = Block::new(BlockType::Grass);
-                    }
+    // Everything about the chunk, including light, in the format region files store.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.put_u8(CHUNK_FORMAT_VERSION);
+        writer.put_i32(self.coord.0);
+        writer.put_i32(self.coord.1);
+        writer.put_i32(MIN_Y);
+        writer.put_u16(SECTION_COUNT as u16);
+        for biome in &self.biomes {
+            writer.put_u8(*biome as u8);
+        }
+        for section in &self.sections {
+            match section {
+                Some(section) => {
+                    writer.put_u8(1);
+                    section.write_to(&mut writer);
                 }
+                None => writer.put_u8(0),
             }
         }
+        writer.into_bytes()
+    }
 
-        let mut rng = rand::rng(); // Per compiler hint
-        const TREE_CHANCE: f64 = 0.02;
-        let mut next_tree_id: u32 = 1;
-
-        for x_coord in 2..(CHUNK_WIDTH - 2) {
-            for z_coord in 2..(CHUNK_DEPTH - 2) {
-                if self.blocks[x_coord][surface_level][z_coord].block_type == BlockType::Grass {
-                    if rng.random_bool(TREE_CHANCE) {
-                        // Per compiler hint
-                        self.place_tree(
-                            x_coord,
-                            surface_level + 1,
-                            z_coord,
-                            next_tree_id,
-                            &mut rng,
-                        );
-                        next_tree_id = next_tree_id.wrapping_add(1);
-                        if next_tree_id == 0 {
-                            next_tree_id = 1;
-                        }
-                    }
+    pub fn decode(bytes: &[u8]) -> io::Result<Chunk> {
+        let mut reader = ByteReader::new(bytes);
+        let version = reader.get_u8()?;
+        if !(1..=CHUNK_FORMAT_VERSION).contains(&version) {
+            return Err(invalid_data("Unsupported chunk format version"));
+        }
+        let coord = (reader.get_i32()?, reader.get_i32()?);
+        if reader.get_i32()? != MIN_Y || reader.get_u16()? as usize != SECTION_COUNT {
+            return Err(invalid_data(
+                "Chunk was saved with a different world height",
+            ));
+        }
+        let biomes = (0..CHUNK_WIDTH * CHUNK_DEPTH)
+            .map(|_| {
+                Biome::from_id(reader.get_u8()?).ok_or_else(|| invalid_data("Unknown biome id"))
+            })
+            .collect::<io::Result<Vec<Biome>>>()?;
+        let sections = (0..SECTION_COUNT)
+            .map(|_| match reader.get_u8()? {
+                0 => Ok(None),
+                1 => {
+                    Section::read_from(&mut reader, version).map(|section| Some(Box::new(section)))
                 }
-            }
+                _ => Err(invalid_data("Invalid section marker")),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        if !reader.is_at_end() {
+            return Err(invalid_data("Trailing bytes after chunk data"));
         }
+        Ok(Chunk {
+            coord,
+            sections,
+            biomes,
+        })
     }
 
-    fn place_tree(&mut self, x: usize, y_base: usize, z: usize, tree_id: u32, rng: &mut impl Rng) {
-        let trunk_height: usize = rng.random_range(3..=5); // Per compiler hint
-        let canopy_radius: isize = 2;
-        let canopy_base_y_offset: usize = trunk_height.saturating_sub(2);
-        let canopy_max_height_above_base: usize = 3;
+    pub fn section_index(y: usize) -> usize {
+        y / SECTION_SIZE
+    }
 
-        let tree_top_y = y_base + trunk_height;
-        if tree_top_y + canopy_max_height_above_base >= CHUNK_HEIGHT {
-            return;
-        }
+    // True if the section has no blocks stored, i.e. it is all open air.
+    pub fn is_section_empty(&self, section_index: usize) -> bool {
+        self.sections
+            .get(section_index)
+            .is_none_or(|section| section.is_none())
+    }
 
-        for i in 0..trunk_height {
-            if y_base + i < CHUNK_HEIGHT {
-                self.set_block(x, y_base + i, z, BlockType::OakLog)
-                    .unwrap_or_default();
+    // Local coordinates of every fluid block. Sections with no fluid in their palette are
+    // skipped without looking at their blocks.
+    pub fn fluid_positions(&self) -> Vec<(usize, usize, usize)> {
+        let mut positions = Vec::new();
+        for (section_index, section) in self.sections.iter().enumerate() {
+            let Some(section) = section else {
+                continue;
+            };
+            if !section
+                .blocks
+                .palette()
+                .iter()
+                .any(|state| state.block_type.definition().fluid)
+            {
+                continue;
+            }
+            for x in 0..CHUNK_WIDTH {
+                for section_y in 0..SECTION_SIZE {
+                    let y = section_index * SECTION_SIZE + section_y;
+                    for z in 0..CHUNK_DEPTH {
+                        let state = section.blocks.get(Section::index(x, y, z));
+                        if state.block_type.definition().fluid {
+                            positions.push((x, y, z));
+                        }
+                    }
+                }
             }
         }
+        positions
+    }
 
-        let canopy_center_y_world = y_base + trunk_height - 1;
-        let y_start_canopy = (y_base + canopy_base_y_offset).max(0);
-        let y_end_canopy = (y_base + trunk_height + 1).min(CHUNK_HEIGHT - 1);
-
-        for ly_world in y_start_canopy..=y_end_canopy {
-            let y_dist_from_canopy_center =
-                (ly_world as isize - canopy_center_y_world as isize).abs();
-            let current_layer_radius = if y_dist_from_canopy_center <= 0 {
-                canopy_radius
-            } else if y_dist_from_canopy_center == 1 {
-                canopy_radius
-            } else {
-                (canopy_radius - 1).max(1)
-            };
-
-            for lx_offset in -current_layer_radius..=current_layer_radius {
-                for lz_offset in -current_layer_radius..=current_layer_radius {
-                    let current_x_world = x as isize + lx_offset;
-                    let current_z_world = z as isize + lz_offset;
+    pub fn stored_section_count(&self) -> usize {
+        self.sections
+            .iter()
+            .filter(|section| section.is_some())
+            .count()
+    }
 
-                    if current_x_world < 0
-                        || current_x_world >= CHUNK_WIDTH as isize
-                        || current_z_world < 0
-                        || current_z_world >= CHUNK_DEPTH as isize
-                    {
-                        continue;
-                    }
+    pub fn memory_usage(&self) -> ChunkMemoryUsage {
+        let mut usage = ChunkMemoryUsage {
+            block_bytes: self.sections.capacity() * std::mem::size_of::<Option<Box<Section>>>(),
+            ..ChunkMemoryUsage::default()
+        };
+        for section in self.sections.iter().flatten() {
+            usage += section.memory_usage();
+        }
+        usage
+    }
 
-                    let (ux, uy, uz) =
-                        (current_x_world as usize, ly_world, current_z_world as usize);
+    fn in_bounds(x: usize, y: usize, z: usize) -> bool {
+        x < CHUNK_WIDTH && y < CHUNK_HEIGHT && z < CHUNK_DEPTH
+    }
 
-                    if lx_offset == 0 && lz_offset == 0 && uy < canopy_center_y_world {
-                        continue;
-                    }
+    // Stores the section containing `y` if it was empty, so it can be written to.
+    fn section_mut(&mut self, y: usize) -> &mut Section {
+        self.sections[Self::section_index(y)].get_or_insert_with(|| Box::new(Section::open_air()))
+    }
 
-                    let dist_sq_horiz = lx_offset * lx_offset + lz_offset * lz_offset; // Defined here
+    pub fn get_biome(&self, x: usize, z: usize) -> Biome {
+        if x < CHUNK_WIDTH && z < CHUNK_DEPTH {
+            self.biomes[x * CHUNK_DEPTH + z]
+        } else {
+            Biome::Plains
+        }
+    }
 
-                    if dist_sq_horiz > current_layer_radius * current_layer_radius {
-                        if y_dist_from_canopy_center > 0 {
-                            continue;
-                        } else if dist_sq_horiz > (canopy_radius + 1) * (canopy_radius + 1) {
-                            continue;
-                        }
-                    }
+    pub fn set_biome(&mut self, x: usize, z: usize, biome: Biome) {
+        if x < CHUNK_WIDTH && z < CHUNK_DEPTH {
+            self.biomes[x * CHUNK_DEPTH + z] = biome;
+        }
+    }
 
-                    let probability = if current_layer_radius == 0 {
-                        0.0
-                    } else {
-                        // Ensure radius - 1 does not underflow for isize if current_layer_radius is 0,
-                        // though current_layer_radius logic already ensures it's at least 1 if not 0.
-                        let r_core_sq = ((current_layer_radius - 1).max(0) as f32).powi(2);
-                        let r_edge_sq = (current_layer_radius as f32).powi(2);
-                        // let r_fuzzy_sq = ((current_layer_radius + 1) as f32).powi(2); // Optional outer fuzzy layer
-
-                        if (dist_sq_horiz as f32) <= r_core_sq {
-                            0.95 // Core part: almost always place
-                        } else if (dist_sq_horiz as f32) <= r_edge_sq {
-                            0.6 // Edge part: moderate chance
-                        // } else if (dist_sq_horiz as f32) <= r_fuzzy_sq { // Uncomment for fuzzier edges
-                        //     0.25 // Fuzzy outer part: lower chance
-                        } else {
-                            0.0 // Outside defined canopy for this layer
-                        }
-                    };
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> Option<Block> {
+        if !Self::in_bounds(x, y, z) {
+            return None;
+        }
+        match &self.sections[Self::section_index(y)] {
+            Some(section) => Some(section.get(Section::index(x, y, z))),
+            None => Some(OPEN_AIR),
+        }
+    }
 
-                    if probability > 0.0 && rng.random_bool(probability) {
-                        if self.blocks[ux][uy][uz].block_type == BlockType::Air {
-                            self.set_block_with_tree_id(ux, uy, uz, BlockType::OakLeaves, tree_id)
-                                .unwrap_or_default();
-                        }
-                    }
-                }
-            }
+    fn write_block(&mut self, x: usize, y: usize, z: usize, block: Block) {
+        // Air written into an empty section changes nothing that needs storing.
+        if block.block_type == BlockType::AIR && self.is_section_empty(Self::section_index(y)) {
+            return;
         }
+        self.section_mut(y).set(Section::index(x, y, z), block);
     }
 
-    pub fn get_block(&self, x: usize, y: usize, z: usize) -> Option<&Block> {
-        if x < CHUNK_WIDTH && y < CHUNK_HEIGHT && z < CHUNK_DEPTH {
-            Some(&self.blocks[x][y][z])
+    pub fn set_block(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: usize,
+        block_type: BlockType,
+    ) -> Result<(), &'static str> {
+        if Self::in_bounds(x, y, z) {
+            self.write_block(x, y, z, Block::new(block_type));
+            Ok(())
         } else {
-            None
+            Err("Coordinates out of chunk bounds")
         }
     }
 
-    pub fn set_block(
+    // A fluid block at the given level (see `Block::fluid_level`).
+    pub fn set_fluid(
         &mut self,
         x: usize,
         y: usize,
         z: usize,
         block_type: BlockType,
+        fluid_level: u8,
     ) -> Result<(), &'static str> {
-        if x < CHUNK_WIDTH && y < CHUNK_HEIGHT && z < CHUNK_DEPTH {
-            self.blocks[x][y][z] = Block::new(block_type);
+        if Self::in_bounds(x, y, z) {
+            self.write_block(x, y, z, Block::new_fluid(block_type, fluid_level));
             Ok(())
         } else {
             Err("Coordinates out of chunk bounds")
@@ -188,23 +397,44 @@ impl Chunk {
         block_type: BlockType,
         tree_id: u32,
     ) -> Result<(), &'static str> {
-        if x < CHUNK_WIDTH && y < CHUNK_HEIGHT && z < CHUNK_DEPTH {
-            self.blocks[x][y][z] = Block::new_with_tree_id(block_type, tree_id);
+        if Self::in_bounds(x, y, z) {
+            self.write_block(x, y, z, Block::new_with_tree_id(block_type, tree_id));
             Ok(())
         } else {
             Err("Coordinates out of chunk bounds")
         }
     }
 
+    // Local y of the highest block that stops sky light, if any.
+    fn highest_opaque_y(&self) -> Option<usize> {
+        (0..SECTION_COUNT).rev().find_map(|section_index| {
+            let section = self.sections[section_index].as_ref()?;
+            (0..SECTION_SIZE).rev().find_map(|section_y| {
+                let y = section_index * SECTION_SIZE + section_y;
+                let has_opaque = (0..CHUNK_WIDTH).any(|x| {
+                    (0..CHUNK_DEPTH)
+                        .any(|z| !section.blocks.get(Section::index(x, y, z)).is_transparent())
+                });
+                has_opaque.then_some(y)
+            })
+        })
+    }
+
     pub fn calculate_sky_light(&mut self) {
         // --- Phase 0: Reset ---
         // Start by setting all sky light levels to 0. This ensures we have a clean slate
         // and that blocks under overhangs aren't prematurely marked as permanently dark.
-        for x in 0..CHUNK_WIDTH {
-            for y in 0..CHUNK_HEIGHT {
-                for z in 0..CHUNK_DEPTH {
-                    self.blocks[x][y][z].sky_light = 0;
-                }
+        // Empty sections above every opaque block stay fully lit and are skipped; the ones
+        // below it may end up in shadow, so they are stored to hold their light.
+        let lowest_open_section = self
+            .highest_opaque_y()
+            .map_or(0, |y| Self::section_index(y) + 1);
+        for (section_index, section) in self.sections.iter_mut().enumerate() {
+            if section_index < lowest_open_section {
+                section.get_or_insert_with(|| Box::new(Section::open_air()));
+            }
+            if let Some(section) = section {
+                section.sky_light.fill(0);
             }
         }
 
@@ -216,12 +446,15 @@ impl Chunk {
         for x in 0..CHUNK_WIDTH {
             for z in 0..CHUNK_DEPTH {
                 for y in (0..CHUNK_HEIGHT).rev() {
-                    let block = &mut self.blocks[x][y][z];
+                    let Some(section) = &mut self.sections[Self::section_index(y)] else {
+                        continue;
+                    };
+                    let index = Section::index(x, y, z);
 
                     // Only transparent blocks can propagate this light to neighbors.
-                    if block.is_transparent() {
+                    if section.blocks.get(index).is_transparent() {
                         // A block with sky access always has a light value of 15.
-                        block.sky_light = 15;
+                        section.sky_light.set(index, 15);
                         light_queue.push_back((x, y, z));
                     } else {
                         // If we hit a solid block, it do not receives light but stops it from
@@ -235,10 +468,10 @@ impl Chunk {
         // --- Phase 2: Propagation Flood Fill ---
         // Spread light from the source blocks in the queue into adjacent dark areas.
         while let Some((x, y, z)) = light_queue.pop_front() {
-            let current_light_level = self.blocks[x][y][z].sky_light;
+            let current_light_level = self.get_block(x, y, z).map_or(0, |b| b.sky_light);
             let neighbor_light_level = current_light_level.saturating_sub(1);
 
-            if neighbor_light_level <= 0 {
+            if neighbor_light_level == 0 {
                 continue; // This light has faded to nothing.
             }
 
@@ -252,29 +485,150 @@ impl Chunk {
                 (x, y, z + 1),
             ];
 
-            // TODO handle blocks from neighboring chunks
+            // Light crossing into neighbouring chunks is spread by `World` once both are
+            // loaded (see `World::insert_chunk`).
 
             for (nx, ny, nz) in neighbors {
-                // Check if the neighbor is within the chunk's bounds
-                if nx < CHUNK_WIDTH && ny < CHUNK_HEIGHT && nz < CHUNK_DEPTH {
-                    let neighbor_block = &mut self.blocks[nx][ny][nz];
-
+                // Check if the neighbor is within the chunk's bounds. Empty sections are
+                // already at full light, so this never has to store a new one.
+                if let Some(neighbor_block) = self.get_block(nx, ny, nz)
+                    && neighbor_block.is_transparent()
+                    && neighbor_block.sky_light < neighbor_light_level
+                {
                     // If the neighbor is transparent and we can make it brighter, update it.
-                    if neighbor_block.is_transparent()
-                        && neighbor_block.sky_light < neighbor_light_level
-                    {
-                        neighbor_block.sky_light = neighbor_light_level;
-                        light_queue.push_back((nx, ny, nz));
-                    }
+                    self.section_mut(ny)
+                        .sky_light
+                        .set(Section::index(nx, ny, nz), neighbor_light_level);
+                    light_queue.push_back((nx, ny, nz));
                 }
-                // Note: Cross-chunk propagation would be handled here by querying the `World`.
             }
         }
+
+        // --- Phase 3: Drop sections that ended up as plain open air ---
+        for section in &mut self.sections {
+            if section
+                .as_ref()
+                .is_some_and(|section| section.is_open_air())
+            {
+                *section = None;
+            }
+        }
+    }
+
+    pub fn set_sky_light(&mut self, x: usize, y: usize, z: usize, sky_light: u8) {
+        if !Self::in_bounds(x, y, z) {
+            return;
+        }
+        if sky_light == OPEN_AIR.sky_light && self.is_section_empty(Self::section_index(y)) {
+            return;
+        }
+        self.section_mut(y)
+            .sky_light
+            .set(Section::index(x, y, z), sky_light);
     }
 
-    pub fn set_block_light(&mut self, x: usize, y: usize, z: usize, sky_light: u8) {
-        if x < CHUNK_WIDTH && y < CHUNK_HEIGHT && z < CHUNK_DEPTH {
-            self.blocks[x][y][z].sky_light = sky_light;
+    pub fn set_block_light(&mut self, x: usize, y: usize, z: usize, block_light: u8) {
+        if !Self::in_bounds(x, y, z) {
+            return;
+        }
+        if block_light == OPEN_AIR.block_light && self.is_section_empty(Self::section_index(y)) {
+            return;
+        }
+        self.section_mut(y)
+            .block_light
+            .set(Section::index(x, y, z), block_light);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_world_y_round_trips_through_local_y() {
+        assert_eq!(world_y_to_local(MIN_Y), Some(0));
+        assert_eq!(world_y_to_local(MAX_Y - 1), Some(CHUNK_HEIGHT - 1));
+        assert_eq!(world_y_to_local(MIN_Y - 1), None);
+        assert_eq!(world_y_to_local(MAX_Y), None);
+        for world_y in [MIN_Y, -1, 0, 1, MAX_Y - 1] {
+            assert_eq!(
+                local_y_to_world(world_y_to_local(world_y).unwrap()),
+                world_y
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_sections_are_not_stored() {
+        let mut chunk = Chunk::new(0, 0);
+        assert_eq!(chunk.stored_section_count(), 0);
+        assert_eq!(
+            chunk.get_block(3, 100, 3).unwrap().block_type,
+            BlockType::AIR
+        );
+
+        // Writing air into an empty section keeps it empty.
+        chunk.set_block(3, 100, 3, BlockType::AIR).unwrap();
+        assert_eq!(chunk.stored_section_count(), 0);
+
+        chunk.set_block(3, 40, 3, BlockType::STONE).unwrap();
+        assert_eq!(chunk.stored_section_count(), 1);
+        assert!(!chunk.is_section_empty(Chunk::section_index(40)));
+        assert_eq!(
+            chunk.get_block(3, 40, 3).unwrap().block_type,
+            BlockType::STONE
+        );
+
+        // Digging it out again leaves an all-air section that lighting drops.
+        chunk.set_block(3, 40, 3, BlockType::AIR).unwrap();
+        chunk.calculate_sky_light();
+        assert_eq!(chunk.stored_section_count(), 0);
+    }
+
+    #[test]
+    fn test_sky_light_reaches_through_empty_sections() {
+        let mut chunk = Chunk::new(0, 0);
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                chunk.set_block(x, 0, z, BlockType::STONE).unwrap();
+            }
+        }
+        // A roof high up, over a single column.
+        chunk.set_block(5, 200, 5, BlockType::STONE).unwrap();
+        chunk.calculate_sky_light();
+
+        assert_eq!(chunk.get_block(5, 201, 5).unwrap().sky_light, 15);
+        assert_eq!(chunk.get_block(5, 199, 5).unwrap().sky_light, 14);
+        assert_eq!(chunk.get_block(6, 199, 5).unwrap().sky_light, 15);
+        assert_eq!(chunk.get_block(5, 0, 5).unwrap().sky_light, 0);
+        // Everything above the roof's section is plain sky again.
+        assert!(chunk.is_section_empty(Chunk::section_index(200) + 1));
+    }
+
+    #[test]
+    fn test_chunk_round_trips_through_encoding() {
+        let mut chunk = Chunk::new(-3, 7);
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                chunk.set_block(x, 0, z, BlockType::STONE).unwrap();
+                chunk.set_block(x, 1, z, BlockType::DIRT).unwrap();
+            }
         }
+        chunk
+            .set_block_with_tree_id(4, 2, 4, BlockType::OAK_LOG, 9)
+            .unwrap();
+        chunk.set_fluid(6, 2, 6, BlockType::WATER, 3).unwrap();
+        chunk.biomes[17] = Biome::Desert;
+        chunk.calculate_sky_light();
+
+        let bytes = chunk.encode();
+        let decoded = Chunk::decode(&bytes).unwrap();
+        assert!(decoded == chunk);
+        assert_eq!(decoded.coord, (-3, 7));
+        assert_eq!(decoded.get_block(4, 2, 4).unwrap().tree_id, Some(9));
+        assert_eq!(decoded.get_block(4, 1, 4).unwrap().tree_id, None);
+        assert_eq!(decoded.get_block(6, 2, 6).unwrap().fluid_level, 3);
+
+        assert!(Chunk::decode(&bytes[..bytes.len() - 1]).is_err());
     }
 }
@@ -0,0 +1,142 @@
+// Decides which chunks around the player should be loaded and which can be dropped. It only
+// reasons about chunk coordinates; `State` performs the actual generation, meshing and
+// eviction of world data and GPU buffers.
+
+pub const DEFAULT_RENDER_DISTANCE: i32 = 4;
+pub const MIN_RENDER_DISTANCE: i32 = 1;
+pub const MAX_RENDER_DISTANCE: i32 = 16;
+
+// Chunks are only unloaded this many chunks beyond the render distance, so walking back
+// and forth across a chunk border does not unload and regenerate the same row every time.
+const UNLOAD_MARGIN: i32 = 2;
+
+// New chunks handed to the generation workers per frame. Keeping the queue short lets it
+// follow the player instead of finishing a backlog of chunks that are no longer wanted.
+const MAX_LOADS_PER_FRAME: usize = 4;
+
+pub struct ChunkManager {
+    render_distance: i32,
+}
+
+impl ChunkManager {
+    pub fn new(render_distance: i32) -> Self {
+        ChunkManager {
+            render_distance: render_distance.clamp(MIN_RENDER_DISTANCE, MAX_RENDER_DISTANCE),
+        }
+    }
+
+    pub fn render_distance(&self) -> i32 {
+        self.render_distance
+    }
+
+    // Returns true if the value changed.
+    pub fn set_render_distance(&mut self, render_distance: i32) -> bool {
+        let clamped = render_distance.clamp(MIN_RENDER_DISTANCE, MAX_RENDER_DISTANCE);
+        let changed = clamped != self.render_distance;
+        self.render_distance = clamped;
+        changed
+    }
+
+    pub fn unload_distance(&self) -> i32 {
+        self.render_distance + UNLOAD_MARGIN
+    }
+
+    // Chunk-grid distance; loading and unloading work on squares around the player.
+    fn distance(center: (i32, i32), coord: (i32, i32)) -> i32 {
+        (coord.0 - center.0).abs().max((coord.1 - center.1).abs())
+    }
+
+    fn distance_sq(center: (i32, i32), coord: (i32, i32)) -> i32 {
+        let dx = coord.0 - center.0;
+        let dz = coord.1 - center.1;
+        dx * dx + dz * dz
+    }
+
+    // Every chunk within the render distance, nearest to the player first.
+    pub fn chunks_in_range(&self, center: (i32, i32)) -> Vec<(i32, i32)> {
+        let radius = self.render_distance;
+        let mut coords: Vec<(i32, i32)> = (-radius..=radius)
+            .flat_map(|dx| (-radius..=radius).map(move |dz| (center.0 + dx, center.1 + dz)))
+            .collect();
+        coords.sort_by_key(|&coord| (Self::distance_sq(center, coord), coord));
+        coords
+    }
+
+    // The nearest chunks in range that are not loaded yet, at most one frame's worth.
+    pub fn chunks_to_load(
+        &self,
+        center: (i32, i32),
+        is_loaded: impl Fn((i32, i32)) -> bool,
+    ) -> Vec<(i32, i32)> {
+        self.chunks_in_range(center)
+            .into_iter()
+            .filter(|&coord| !is_loaded(coord))
+            .take(MAX_LOADS_PER_FRAME)
+            .collect()
+    }
+
+    pub fn is_within_unload_distance(&self, center: (i32, i32), coord: (i32, i32)) -> bool {
+        Self::distance(center, coord) <= self.unload_distance()
+    }
+
+    // Loaded chunks that drifted beyond the unload distance.
+    pub fn chunks_to_unload(
+        &self,
+        center: (i32, i32),
+        loaded: impl IntoIterator<Item = (i32, i32)>,
+    ) -> Vec<(i32, i32)> {
+        loaded
+            .into_iter()
+            .filter(|&coord| !self.is_within_unload_distance(center, coord))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_load_nearest_first() {
+        let manager = ChunkManager::new(3);
+        let in_range = manager.chunks_in_range((10, -4));
+        assert_eq!(in_range.len(), 7 * 7);
+        assert_eq!(in_range[0], (10, -4));
+        let distances: Vec<i32> = in_range
+            .iter()
+            .map(|&coord| ChunkManager::distance_sq((10, -4), coord))
+            .collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let to_load = manager.chunks_to_load((0, 0), |coord| coord == (0, 0));
+        assert_eq!(to_load.len(), MAX_LOADS_PER_FRAME);
+        for coord in to_load {
+            assert_eq!(ChunkManager::distance_sq((0, 0), coord), 1);
+        }
+    }
+
+    #[test]
+    fn test_unload_uses_hysteresis() {
+        let manager = ChunkManager::new(2);
+        // Just outside the render distance: not loaded anew, but kept if already loaded.
+        let just_outside = (3, 0);
+        assert!(!manager.chunks_in_range((0, 0)).contains(&just_outside));
+        assert!(manager.chunks_to_unload((0, 0), [just_outside]).is_empty());
+
+        let beyond = (0, -(manager.unload_distance() + 1));
+        assert_eq!(
+            manager.chunks_to_unload((0, 0), [just_outside, beyond]),
+            vec![beyond]
+        );
+    }
+
+    #[test]
+    fn test_render_distance_is_clamped() {
+        let mut manager = ChunkManager::new(100);
+        assert_eq!(manager.render_distance(), MAX_RENDER_DISTANCE);
+        assert!(manager.set_render_distance(3));
+        assert!(!manager.set_render_distance(3));
+        manager.set_render_distance(0);
+        assert_eq!(manager.render_distance(), MIN_RENDER_DISTANCE);
+    }
+}
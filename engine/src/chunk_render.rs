@@ -0,0 +1,227 @@
+// GPU side of chunk meshing: uploads the CPU mesh data built by `mesher` into vertex and
+// index buffers and draws them. No meshing decisions are made here.
+
+use crate::Vertex;
+use crate::mesher::{ChunkMesh, TransparentQuads};
+use crate::physics::AABB;
+use crate::visibility::SectionVisibility;
+use glam::{IVec3, Vec3};
+use std::ops::Range;
+use wgpu::util::DeviceExt;
+
+pub struct ChunkRenderBuffers {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
+    pub num_indices: u32,
+}
+
+impl ChunkRenderBuffers {
+    // None for an empty mesh, which has nothing to draw.
+    fn upload(
+        device: &wgpu::Device,
+        label: &str,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> Option<Self> {
+        if vertices.is_empty() || indices.is_empty() {
+            return None;
+        }
+        let index_format = index_format_for(vertices.len());
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} VB", label)),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} IB", label)),
+            contents: &index_bytes(index_format, indices),
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+        });
+        Some(ChunkRenderBuffers {
+            vertex_buffer,
+            index_buffer,
+            index_format,
+            num_indices: indices.len() as u32,
+        })
+    }
+
+    // Replaces the index buffer with a reordering of the same indices.
+    fn write_indices(&self, queue: &wgpu::Queue, indices: &[u32]) {
+        debug_assert_eq!(indices.len() as u32, self.num_indices);
+        queue.write_buffer(
+            &self.index_buffer,
+            0,
+            &index_bytes(self.index_format, indices),
+        );
+    }
+
+    pub fn draw<'pass>(&'pass self, render_pass: &mut wgpu::RenderPass<'pass>) {
+        self.draw_ranges(render_pass, std::iter::once(0..self.num_indices));
+    }
+
+    pub fn draw_ranges<'pass>(
+        &'pass self,
+        render_pass: &mut wgpu::RenderPass<'pass>,
+        ranges: impl IntoIterator<Item = Range<u32>>,
+    ) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+        for range in ranges {
+            render_pass.draw_indexed(range, 0, 0..1);
+        }
+    }
+}
+
+pub struct ChunkRenderData {
+    pub opaque_buffers: Option<ChunkRenderBuffers>,
+    pub transparent_buffers: Option<ChunkRenderBuffers>,
+    pub fluid_buffers: Option<ChunkRenderBuffers>,
+    // Box around all of the chunk's geometry, for frustum culling. None if there is nothing
+    // to draw.
+    pub bounds: Option<AABB>,
+    pub section_visibility: Vec<SectionVisibility>,
+    opaque_section_ranges: Vec<Range<u32>>,
+    transparent_quads: TransparentQuads,
+    fluid_quads: TransparentQuads,
+    // Block the camera was in when the transparent and fluid quads were last sorted.
+    transparent_sorted_for: Option<IVec3>,
+}
+
+impl ChunkRenderData {
+    pub fn upload(device: &wgpu::Device, coord: (i32, i32), mesh: &ChunkMesh) -> Self {
+        ChunkRenderData {
+            opaque_buffers: ChunkRenderBuffers::upload(
+                device,
+                &format!("Opaque Chunk ({}, {})", coord.0, coord.1),
+                &mesh.opaque_vertices,
+                &mesh.opaque_indices,
+            ),
+            transparent_buffers: ChunkRenderBuffers::upload(
+                device,
+                &format!("Transparent Chunk ({}, {})", coord.0, coord.1),
+                &mesh.transparent_vertices,
+                &mesh.transparent_indices,
+            ),
+            fluid_buffers: ChunkRenderBuffers::upload(
+                device,
+                &format!("Fluid Chunk ({}, {})", coord.0, coord.1),
+                &mesh.fluid_vertices,
+                &mesh.fluid_indices,
+            ),
+            bounds: mesh_bounds(mesh),
+            section_visibility: mesh.section_visibility.clone(),
+            opaque_section_ranges: mesh.opaque_section_ranges.clone(),
+            transparent_quads: TransparentQuads::new(
+                &mesh.transparent_vertices,
+                &mesh.transparent_indices,
+            ),
+            fluid_quads: TransparentQuads::new(&mesh.fluid_vertices, &mesh.fluid_indices),
+            transparent_sorted_for: None,
+        }
+    }
+
+    // Sorts the transparent and fluid quads back to front, but only once the camera has moved into
+    // another block since the last sort; within a block the order barely changes.
+    pub fn sort_transparent(&mut self, queue: &wgpu::Queue, camera: Vec3) {
+        let camera_block = camera.floor().as_ivec3();
+        if self.transparent_sorted_for == Some(camera_block) {
+            return;
+        }
+        self.transparent_sorted_for = Some(camera_block);
+        if let Some(transparent_buffers) = &self.transparent_buffers {
+            transparent_buffers
+                .write_indices(queue, &self.transparent_quads.sorted_indices(camera));
+        }
+        if let Some(fluid_buffers) = &self.fluid_buffers {
+            fluid_buffers.write_indices(queue, &self.fluid_quads.sorted_indices(camera));
+        }
+    }
+
+    // Draws the opaque geometry of the sections for which `is_visible` holds, merging runs
+    // of visible sections into a single draw call.
+    pub fn draw_opaque_sections<'pass>(
+        &'pass self,
+        render_pass: &mut wgpu::RenderPass<'pass>,
+        is_visible: impl Fn(usize) -> bool,
+    ) {
+        let Some(opaque_buffers) = &self.opaque_buffers else {
+            return;
+        };
+        opaque_buffers.draw_ranges(
+            render_pass,
+            merged_ranges(
+                self.opaque_section_ranges
+                    .iter()
+                    .enumerate()
+                    .filter(|(section, _)| is_visible(*section))
+                    .map(|(_, range)| range.clone()),
+            ),
+        );
+    }
+}
+
+// Joins ranges that follow on from each other and drops empty ones.
+fn merged_ranges(ranges: impl Iterator<Item = Range<u32>>) -> Vec<Range<u32>> {
+    let mut merged: Vec<Range<u32>> = Vec::new();
+    for range in ranges.filter(|range| !range.is_empty()) {
+        match merged.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+fn mesh_bounds(mesh: &ChunkMesh) -> Option<AABB> {
+    let mut positions = mesh
+        .opaque_vertices
+        .iter()
+        .chain(&mesh.transparent_vertices)
+        .chain(&mesh.fluid_vertices)
+        .map(|vertex| Vec3::from(vertex.position));
+    let first = positions.next()?;
+    let (min, max) = positions.fold((first, first), |(min, max), position| {
+        (min.min(position), max.max(position))
+    });
+    Some(AABB::new(min, max))
+}
+
+// Indices as uploaded, narrowed to 16 bits for `Uint16` buffers.
+fn index_bytes(index_format: wgpu::IndexFormat, indices: &[u32]) -> Vec<u8> {
+    match index_format {
+        wgpu::IndexFormat::Uint16 => indices
+            .iter()
+            .flat_map(|&index| (index as u16).to_ne_bytes())
+            .collect(),
+        wgpu::IndexFormat::Uint32 => bytemuck::cast_slice(indices).to_vec(),
+    }
+}
+
+// 16-bit indices halve the index buffer and cover almost every chunk; only meshes with more
+// vertices than they can address need 32-bit ones.
+fn index_format_for(vertex_count: usize) -> wgpu::IndexFormat {
+    if vertex_count <= u16::MAX as usize + 1 {
+        wgpu::IndexFormat::Uint16
+    } else {
+        wgpu::IndexFormat::Uint32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_format_switches_to_32_bit_past_u16_range() {
+        assert_eq!(index_format_for(4), wgpu::IndexFormat::Uint16);
+        assert_eq!(index_format_for(65_536), wgpu::IndexFormat::Uint16);
+        assert_eq!(index_format_for(65_537), wgpu::IndexFormat::Uint32);
+    }
+
+    #[test]
+    fn test_merged_ranges_join_adjacent_sections() {
+        let ranges = [0..6, 6..6, 6..12, 18..24];
+        assert_eq!(merged_ranges(ranges.into_iter()), vec![0..12, 18..24]);
+    }
+}
@@ -0,0 +1,329 @@
+// Compact containers for per-block data inside a chunk section. Blocks are stored as small
+// indices into a palette of the distinct values the section holds, bit-packed into u64
+// words; light levels are 4-bit nibbles, two per byte.
+
+use crate::save::codec::{ByteReader, ByteWriter, invalid_data};
+use std::io;
+
+// Indices wider than this would mean more distinct values than a section has blocks.
+const MAX_BITS_PER_ENTRY: u32 = 16;
+
+// A fixed number of values, stored as indices into a palette of distinct values. A section
+// made of a single value needs no index data at all; the index width grows as the palette
+// does.
+#[derive(Clone)]
+pub struct PalettedStorage<T> {
+    len: usize,
+    palette: Vec<T>,
+    bits_per_entry: u32,
+    // Entries never straddle two words, so each word holds `64 / bits_per_entry` of them.
+    words: Vec<u64>,
+}
+
+impl<T: Copy + PartialEq> PalettedStorage<T> {
+    pub fn new(len: usize, value: T) -> Self {
+        PalettedStorage {
+            len,
+            palette: vec![value],
+            bits_per_entry: 0,
+            words: Vec::new(),
+        }
+    }
+
+    pub fn palette(&self) -> &[T] {
+        &self.palette
+    }
+
+    fn entries_per_word(bits_per_entry: u32) -> usize {
+        (64 / bits_per_entry) as usize
+    }
+
+    fn read_index(&self, index: usize) -> usize {
+        if self.bits_per_entry == 0 {
+            return 0;
+        }
+        let per_word = Self::entries_per_word(self.bits_per_entry);
+        let shift = (index % per_word) as u32 * self.bits_per_entry;
+        let mask = (1u64 << self.bits_per_entry) - 1;
+        ((self.words[index / per_word] >> shift) & mask) as usize
+    }
+
+    fn write_index(&mut self, index: usize, palette_index: usize) {
+        let per_word = Self::entries_per_word(self.bits_per_entry);
+        let shift = (index % per_word) as u32 * self.bits_per_entry;
+        let mask = ((1u64 << self.bits_per_entry) - 1) << shift;
+        let word = &mut self.words[index / per_word];
+        *word = (*word & !mask) | ((palette_index as u64) << shift);
+    }
+
+    pub fn get(&self, index: usize) -> T {
+        self.palette[self.read_index(index)]
+    }
+
+    pub fn set(&mut self, index: usize, value: T) {
+        let palette_index = match self.palette.iter().position(|entry| *entry == value) {
+            Some(palette_index) => palette_index,
+            None => self.add_to_palette(value),
+        };
+        if self.bits_per_entry > 0 {
+            self.write_index(index, palette_index);
+        }
+    }
+
+    // Makes room for `value` in the palette and returns its index. Entries nothing refers
+    // to any more are dropped first; the index width only grows if that is not enough.
+    fn add_to_palette(&mut self, value: T) -> usize {
+        let capacity = 1usize << self.bits_per_entry;
+        if self.palette.len() == capacity {
+            self.remove_unused_entries();
+        }
+        if self.palette.len() == 1usize << self.bits_per_entry {
+            self.repack(self.bits_per_entry + 1);
+        }
+        self.palette.push(value);
+        self.palette.len() - 1
+    }
+
+    fn remove_unused_entries(&mut self) {
+        let mut used = vec![false; self.palette.len()];
+        for index in 0..self.len {
+            used[self.read_index(index)] = true;
+        }
+        if used.iter().all(|&used| used) {
+            return;
+        }
+        let values: Vec<T> = (0..self.len).map(|index| self.get(index)).collect();
+        self.palette.retain({
+            let mut used = used.into_iter();
+            move |_| used.next().unwrap_or(false)
+        });
+        self.rewrite(&values);
+    }
+
+    fn repack(&mut self, bits_per_entry: u32) {
+        let values: Vec<T> = (0..self.len).map(|index| self.get(index)).collect();
+        self.bits_per_entry = bits_per_entry;
+        self.rewrite(&values);
+    }
+
+    // Re-encodes `values` with the current palette and index width.
+    fn rewrite(&mut self, values: &[T]) {
+        if self.bits_per_entry == 0 {
+            self.words = Vec::new();
+            return;
+        }
+        let per_word = Self::entries_per_word(self.bits_per_entry);
+        self.words = vec![0; self.len.div_ceil(per_word)];
+        for (index, value) in values.iter().enumerate() {
+            let palette_index = self
+                .palette
+                .iter()
+                .position(|entry| entry == value)
+                .expect("Every stored value is in the palette");
+            self.write_index(index, palette_index);
+        }
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        self.palette.capacity() * std::mem::size_of::<T>()
+            + self.words.capacity() * std::mem::size_of::<u64>()
+    }
+
+    // Palette, index width and packed words, as they are in memory.
+    pub fn write_to(&self, writer: &mut ByteWriter, write_value: impl Fn(&mut ByteWriter, &T)) {
+        writer.put_u16(self.palette.len() as u16);
+        for value in &self.palette {
+            write_value(writer, value);
+        }
+        writer.put_u8(self.bits_per_entry as u8);
+        for word in &self.words {
+            writer.put_u64(*word);
+        }
+    }
+
+    pub fn read_from(
+        reader: &mut ByteReader,
+        len: usize,
+        read_value: impl Fn(&mut ByteReader) -> io::Result<T>,
+    ) -> io::Result<Self> {
+        let palette_len = reader.get_u16()? as usize;
+        let palette = (0..palette_len)
+            .map(|_| read_value(reader))
+            .collect::<io::Result<Vec<T>>>()?;
+        let bits_per_entry = reader.get_u8()? as u32;
+        if palette.is_empty()
+            || bits_per_entry > MAX_BITS_PER_ENTRY
+            || palette.len() > 1 << bits_per_entry
+        {
+            return Err(invalid_data("Invalid block palette"));
+        }
+        let word_count = if bits_per_entry == 0 {
+            0
+        } else {
+            len.div_ceil(Self::entries_per_word(bits_per_entry))
+        };
+        let words = (0..word_count)
+            .map(|_| reader.get_u64())
+            .collect::<io::Result<Vec<u64>>>()?;
+
+        let storage = PalettedStorage {
+            len,
+            palette,
+            bits_per_entry,
+            words,
+        };
+        if (0..len).any(|index| storage.read_index(index) >= storage.palette.len()) {
+            return Err(invalid_data("Block index outside of the palette"));
+        }
+        Ok(storage)
+    }
+}
+
+// Equal contents compare equal, however the palettes happen to be laid out.
+impl<T: Copy + PartialEq> PartialEq for PalettedStorage<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && (0..self.len).all(|index| self.get(index) == other.get(index))
+    }
+}
+
+// 4-bit values, two per byte. A uniform array stores just its value.
+#[derive(Clone)]
+pub struct NibbleArray {
+    len: usize,
+    uniform: u8,
+    bytes: Vec<u8>,
+}
+
+impl NibbleArray {
+    pub fn filled(len: usize, value: u8) -> Self {
+        NibbleArray {
+            len,
+            uniform: value & 0x0F,
+            bytes: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        if self.bytes.is_empty() {
+            return self.uniform;
+        }
+        (self.bytes[index / 2] >> ((index % 2) * 4)) & 0x0F
+    }
+
+    pub fn set(&mut self, index: usize, value: u8) {
+        let value = value & 0x0F;
+        if self.bytes.is_empty() {
+            if value == self.uniform {
+                return;
+            }
+            self.bytes = vec![self.uniform | (self.uniform << 4); self.len.div_ceil(2)];
+        }
+        let shift = (index % 2) * 4;
+        let byte = &mut self.bytes[index / 2];
+        *byte = (*byte & !(0x0F << shift)) | (value << shift);
+    }
+
+    pub fn fill(&mut self, value: u8) {
+        self.uniform = value & 0x0F;
+        self.bytes = Vec::new();
+    }
+
+    // True if every entry equals `value`.
+    pub fn is_filled_with(&self, value: u8) -> bool {
+        if self.bytes.is_empty() {
+            return self.uniform == value;
+        }
+        (0..self.len).all(|index| self.get(index) == value)
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    pub fn write_to(&self, writer: &mut ByteWriter) {
+        if self.bytes.is_empty() {
+            writer.put_u8(0);
+            writer.put_u8(self.uniform);
+        } else {
+            writer.put_u8(1);
+            writer.put_bytes(&self.bytes);
+        }
+    }
+
+    pub fn read_from(reader: &mut ByteReader, len: usize) -> io::Result<Self> {
+        let mut nibbles = NibbleArray::filled(len, 0);
+        match reader.get_u8()? {
+            0 => nibbles.uniform = reader.get_u8()? & 0x0F,
+            1 => nibbles.bytes = reader.take(len.div_ceil(2))?.to_vec(),
+            _ => return Err(invalid_data("Invalid light data")),
+        }
+        Ok(nibbles)
+    }
+}
+
+impl PartialEq for NibbleArray {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && (0..self.len).all(|index| self.get(index) == other.get(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paletted_storage_grows_with_its_palette() {
+        let mut storage = PalettedStorage::new(4096, 0u16);
+        assert_eq!(storage.bits_per_entry, 0);
+        assert_eq!(storage.heap_bytes(), std::mem::size_of::<u16>());
+
+        for index in 0..4096 {
+            storage.set(index, (index % 5) as u16);
+        }
+        assert_eq!(storage.bits_per_entry, 3);
+        assert_eq!(storage.palette().len(), 5);
+        for index in 0..4096 {
+            assert_eq!(storage.get(index), (index % 5) as u16);
+        }
+    }
+
+    #[test]
+    fn test_paletted_storage_reuses_unused_entries() {
+        let mut storage = PalettedStorage::new(64, 0u16);
+        storage.set(0, 1);
+        assert_eq!(storage.bits_per_entry, 1);
+        // Value 1 is no longer used, so value 2 takes its slot instead of widening indices.
+        storage.set(0, 0);
+        storage.set(1, 2);
+        assert_eq!(storage.bits_per_entry, 1);
+        assert_eq!(storage.palette(), &[0, 2]);
+        assert_eq!(storage.get(0), 0);
+        assert_eq!(storage.get(1), 2);
+
+        let mut same_values = PalettedStorage::new(64, 2u16);
+        same_values.set(1, 2);
+        for index in (0..64).filter(|&index| index != 1) {
+            same_values.set(index, 0);
+        }
+        assert!(storage == same_values);
+    }
+
+    #[test]
+    fn test_nibble_array_round_trips() {
+        let mut nibbles = NibbleArray::filled(4096, 15);
+        assert!(nibbles.is_filled_with(15));
+        assert_eq!(nibbles.heap_bytes(), 0);
+
+        nibbles.set(7, 3);
+        nibbles.set(8, 0);
+        assert_eq!(nibbles.get(6), 15);
+        assert_eq!(nibbles.get(7), 3);
+        assert_eq!(nibbles.get(8), 0);
+        assert_eq!(nibbles.heap_bytes(), 2048);
+        assert!(!nibbles.is_filled_with(15));
+
+        nibbles.fill(0);
+        assert!(nibbles.is_filled_with(0));
+        assert_eq!(nibbles.heap_bytes(), 0);
+    }
+}
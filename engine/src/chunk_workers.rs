@@ -0,0 +1,271 @@
+// Background threads for the expensive parts of streaming chunks in: loading or generating
+// terrain and CPU meshing. The main thread submits jobs, polls finished results once per frame and only
+// does the cheap bookkeeping and GPU uploads itself.
+
+use crate::chunk::Chunk;
+use crate::mesher::{ChunkMesh, ChunkNeighbourhood, MeshSettings, build_chunk_mesh};
+use crate::save::WorldSave;
+use crate::world::load_or_generate_chunk_data;
+use crate::worldgen::WorldGenerator;
+use crate::worldgen::structures::BlockPlacement;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+const MAX_WORKER_THREADS: usize = 4;
+
+enum Job {
+    Generate {
+        coord: (i32, i32),
+        cancelled: Arc<AtomicBool>,
+    },
+    Mesh {
+        neighbourhood: ChunkNeighbourhood,
+        settings: MeshSettings,
+        revision: u64,
+        cancelled: Arc<AtomicBool>,
+    },
+}
+
+enum JobResult {
+    Generated {
+        chunk: Chunk,
+        placements: Vec<BlockPlacement>,
+    },
+    Meshed {
+        coord: (i32, i32),
+        revision: u64,
+        mesh: ChunkMesh,
+    },
+}
+
+pub enum ChunkWorkResult {
+    // Ready for `World::insert_chunk`.
+    Generated {
+        chunk: Chunk,
+        placements: Vec<BlockPlacement>,
+    },
+    // Ready for upload.
+    Meshed {
+        coord: (i32, i32),
+        mesh: ChunkMesh,
+    },
+}
+
+pub struct ChunkWorkers {
+    job_sender: Option<Sender<Job>>,
+    result_receiver: Receiver<JobResult>,
+    threads: Vec<JoinHandle<()>>,
+    pending_generations: HashMap<(i32, i32), Arc<AtomicBool>>,
+    // Only the newest mesh request for a chunk is wanted; older ones are cancelled.
+    pending_meshes: HashMap<(i32, i32), (u64, Arc<AtomicBool>)>,
+    next_mesh_revision: u64,
+}
+
+impl ChunkWorkers {
+    pub fn new(
+        generator: Arc<dyn WorldGenerator>,
+        seed: u64,
+        save: Option<Arc<WorldSave>>,
+    ) -> Self {
+        let thread_count = thread::available_parallelism()
+            .map_or(1, |count| count.get().saturating_sub(1))
+            .clamp(1, MAX_WORKER_THREADS);
+
+        let (job_sender, job_receiver) = mpsc::channel::<Job>();
+        let (result_sender, result_receiver) = mpsc::channel::<JobResult>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let threads = (0..thread_count)
+            .map(|index| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let result_sender = result_sender.clone();
+                let generator = Arc::clone(&generator);
+                let save = save.clone();
+                thread::Builder::new()
+                    .name(format!("chunk-worker-{}", index))
+                    .spawn(move || {
+                        worker_loop(
+                            &job_receiver,
+                            &result_sender,
+                            generator.as_ref(),
+                            seed,
+                            save.as_deref(),
+                        )
+                    })
+                    .expect("Failed to spawn chunk worker thread")
+            })
+            .collect();
+
+        ChunkWorkers {
+            job_sender: Some(job_sender),
+            result_receiver,
+            threads,
+            pending_generations: HashMap::new(),
+            pending_meshes: HashMap::new(),
+            next_mesh_revision: 0,
+        }
+    }
+
+    fn submit(&self, job: Job) {
+        if let Some(sender) = &self.job_sender {
+            // Sending only fails once every worker has exited, i.e. while shutting down.
+            let _ = sender.send(job);
+        }
+    }
+
+    pub fn is_generating(&self, coord: (i32, i32)) -> bool {
+        self.pending_generations.contains_key(&coord)
+    }
+
+    pub fn is_meshing(&self, coord: (i32, i32)) -> bool {
+        self.pending_meshes.contains_key(&coord)
+    }
+
+    pub fn pending_generation_count(&self) -> usize {
+        self.pending_generations.len()
+    }
+
+    pub fn request_generation(&mut self, coord: (i32, i32)) {
+        if self.is_generating(coord) {
+            return;
+        }
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.pending_generations
+            .insert(coord, Arc::clone(&cancelled));
+        self.submit(Job::Generate { coord, cancelled });
+    }
+
+    pub fn request_mesh(&mut self, neighbourhood: ChunkNeighbourhood, settings: MeshSettings) {
+        let coord = neighbourhood.center.coord;
+        self.cancel_mesh(coord);
+        self.next_mesh_revision += 1;
+        let revision = self.next_mesh_revision;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.pending_meshes
+            .insert(coord, (revision, Arc::clone(&cancelled)));
+        self.submit(Job::Mesh {
+            neighbourhood,
+            settings,
+            revision,
+            cancelled,
+        });
+    }
+
+    pub fn cancel_mesh(&mut self, coord: (i32, i32)) {
+        if let Some((_, cancelled)) = self.pending_meshes.remove(&coord) {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    // Drops every job for a chunk that left the loaded area. Jobs already running finish,
+    // but their results are discarded.
+    pub fn cancel(&mut self, coord: (i32, i32)) {
+        if let Some(cancelled) = self.pending_generations.remove(&coord) {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+        self.cancel_mesh(coord);
+    }
+
+    // Cancels queued generations whose chunk no longer passes `keep`.
+    pub fn retain_generations(&mut self, mut keep: impl FnMut((i32, i32)) -> bool) {
+        self.pending_generations.retain(|&coord, cancelled| {
+            let wanted = keep(coord);
+            if !wanted {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            wanted
+        });
+    }
+
+    // Finished results that are still wanted.
+    pub fn poll(&mut self) -> Vec<ChunkWorkResult> {
+        let mut results = Vec::new();
+        while let Ok(result) = self.result_receiver.try_recv() {
+            match result {
+                JobResult::Generated { chunk, placements } => {
+                    if self.pending_generations.remove(&chunk.coord).is_some() {
+                        results.push(ChunkWorkResult::Generated { chunk, placements });
+                    }
+                }
+                JobResult::Meshed {
+                    coord,
+                    revision,
+                    mesh,
+                } => {
+                    if self
+                        .pending_meshes
+                        .get(&coord)
+                        .is_some_and(|(pending_revision, _)| *pending_revision == revision)
+                    {
+                        self.pending_meshes.remove(&coord);
+                        results.push(ChunkWorkResult::Meshed { coord, mesh });
+                    }
+                }
+            }
+        }
+        results
+    }
+}
+
+impl Drop for ChunkWorkers {
+    fn drop(&mut self) {
+        for cancelled in self.pending_generations.values() {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+        for (_, cancelled) in self.pending_meshes.values() {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+        // Closing the channel makes every worker's `recv` fail, which ends its loop.
+        self.job_sender = None;
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn worker_loop(
+    job_receiver: &Mutex<Receiver<Job>>,
+    result_sender: &Sender<JobResult>,
+    generator: &dyn WorldGenerator,
+    seed: u64,
+    save: Option<&WorldSave>,
+) {
+    loop {
+        // Hold the lock only while waiting for the next job, not while working on it.
+        let job = match job_receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+        let result = match job {
+            Err(_) => return,
+            Ok(Job::Generate { coord, cancelled }) => {
+                if cancelled.load(Ordering::Relaxed) {
+                    continue;
+                }
+                let (chunk, placements) = load_or_generate_chunk_data(generator, seed, save, coord);
+                JobResult::Generated { chunk, placements }
+            }
+            Ok(Job::Mesh {
+                neighbourhood,
+                settings,
+                revision,
+                cancelled,
+            }) => {
+                if cancelled.load(Ordering::Relaxed) {
+                    continue;
+                }
+                JobResult::Meshed {
+                    coord: neighbourhood.center.coord,
+                    revision,
+                    mesh: build_chunk_mesh(&neighbourhood, settings),
+                }
+            }
+        };
+        if result_sender.send(result).is_err() {
+            return;
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::Vertex;
+use glam::IVec3;
 
 // 8 corners of a cube, unit size centered at origin
 const CUBE_VERTICES_DATA: &[Vertex] = &[
@@ -6,181 +7,253 @@ const CUBE_VERTICES_DATA: &[Vertex] = &[
     // Order: BL, TL, TR, BR
     Vertex {
         position: [-0.5, -0.5, -0.5],
-        color: [1.0, 0.0, 0.0],
+        tint: [1.0, 0.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 0 (orig V0)
     Vertex {
         position: [-0.5, 0.5, -0.5],
-        color: [1.0, 0.0, 0.0],
+        tint: [1.0, 0.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 1 (orig V3)
     Vertex {
         position: [0.5, 0.5, -0.5],
-        color: [1.0, 0.0, 0.0],
+        tint: [1.0, 0.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 2 (orig V2)
     Vertex {
         position: [0.5, -0.5, -0.5],
-        color: [1.0, 0.0, 0.0],
+        tint: [1.0, 0.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 3 (orig V1)
     // Back face (Z = 0.5) - CCW from back (viewing towards -Z, normal 0,0,1)
     // Order: BL, BR, TR, TL (relative to its own view, e.g. V4,V5,V6,V7 is already this)
     Vertex {
         position: [-0.5, -0.5, 0.5],
-        color: [0.0, 1.0, 0.0],
+        tint: [0.0, 1.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 4 (orig V4)
     Vertex {
         position: [0.5, -0.5, 0.5],
-        color: [0.0, 1.0, 0.0],
+        tint: [0.0, 1.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 5 (orig V5)
     Vertex {
         position: [0.5, 0.5, 0.5],
-        color: [0.0, 1.0, 0.0],
+        tint: [0.0, 1.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 6 (orig V6)
     Vertex {
         position: [-0.5, 0.5, 0.5],
-        color: [0.0, 1.0, 0.0],
+        tint: [0.0, 1.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 7 (orig V7) - This order is correct
     // Right face (X = 0.5) - REORDERED FOR CCW from right (viewing towards -X, normal 1,0,0)
     // Order: BFR, TFR, TBR, BBR (Bottom-Front, Top-Front, Top-Back, Bottom-Back)
     Vertex {
         position: [0.5, -0.5, -0.5],
-        color: [0.0, 0.0, 1.0],
+        tint: [0.0, 0.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 8 (orig V8)
     Vertex {
         position: [0.5, 0.5, -0.5],
-        color: [0.0, 0.0, 1.0],
+        tint: [0.0, 0.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 9 (orig V11)
     Vertex {
         position: [0.5, 0.5, 0.5],
-        color: [0.0, 0.0, 1.0],
+        tint: [0.0, 0.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 10 (orig V10)
     Vertex {
         position: [0.5, -0.5, 0.5],
-        color: [0.0, 0.0, 1.0],
+        tint: [0.0, 0.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 11 (orig V9)
     // Left face (X = -0.5) - REORDERED FOR CCW from left (viewing towards +X, normal -1,0,0)
     // Order: BBL, TBL, TFL, BFL (Bottom-Back, Top-Back, Top-Front, Bottom-Front)
     Vertex {
         position: [-0.5, -0.5, 0.5],
-        color: [1.0, 1.0, 0.0],
+        tint: [1.0, 1.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 12 (orig V12)
     Vertex {
         position: [-0.5, 0.5, 0.5],
-        color: [1.0, 1.0, 0.0],
+        tint: [1.0, 1.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 13 (orig V15)
     Vertex {
         position: [-0.5, 0.5, -0.5],
-        color: [1.0, 1.0, 0.0],
+        tint: [1.0, 1.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 14 (orig V14)
     Vertex {
         position: [-0.5, -0.5, -0.5],
-        color: [1.0, 1.0, 0.0],
+        tint: [1.0, 1.0, 0.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 15 (orig V13)
     // Top face (Y = 0.5) - REORDERED FOR CCW from above (viewing towards -Y, normal 0,1,0)
     // Order: Near-Left, Near-Right, Far-Right, Far-Left (relative to view from +Y)
     Vertex {
         position: [-0.5, 0.5, 0.5],
-        color: [1.0, 0.0, 1.0],
+        tint: [1.0, 0.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 16 (Original V19)
     Vertex {
         position: [0.5, 0.5, 0.5],
-        color: [1.0, 0.0, 1.0],
+        tint: [1.0, 0.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 17 (Original V18)
     Vertex {
         position: [0.5, 0.5, -0.5],
-        color: [1.0, 0.0, 1.0],
+        tint: [1.0, 0.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 18 (Original V17)
     Vertex {
         position: [-0.5, 0.5, -0.5],
-        color: [1.0, 0.0, 1.0],
+        tint: [1.0, 0.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 19 (Original V16)
     // Bottom face (Y = -0.5) - REORDERED FOR CCW from below (viewing towards +Y, normal 0,-1,0)
     // Order: Near-Left, Far-Left, Far-Right, Near-Right (relative to view from -Y)
     Vertex {
         position: [-0.5, -0.5, 0.5],
-        color: [0.0, 1.0, 1.0],
+        tint: [0.0, 1.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 20 (Original V20)
     Vertex {
         position: [-0.5, -0.5, -0.5],
-        color: [0.0, 1.0, 1.0],
+        tint: [0.0, 1.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 21 (Original V23)
     Vertex {
         position: [0.5, -0.5, -0.5],
-        color: [0.0, 1.0, 1.0],
+        tint: [0.0, 1.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 22 (Original V22)
     Vertex {
         position: [0.5, -0.5, 0.5],
-        color: [0.0, 1.0, 1.0],
+        tint: [0.0, 1.0, 1.0],
         uv: [0.0, 0.0],
         tree_id: 0,
-        sky_light: 0,
+        sky_light: 0.0,
+        block_light: 0.0,
+        ao: 0.0,
+        atlas_tile: [0.0, 0.0],
     }, // 23 (Original V21)
 ];
 
@@ -233,6 +306,8 @@ const FACE_VERTEX_START_INDICES: [usize; 6] = [
 // The indices for a single face (two triangles), assuming vertices are ordered 0, 1, 2, 3
 // in a quad (e.g., bottom-left, bottom-right, top-right, top-left for CCW)
 const LOCAL_FACE_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+// The same quad split along the other diagonal.
+const FLIPPED_LOCAL_FACE_INDICES: [u16; 6] = [1, 2, 3, 1, 3, 0];
 
 impl CubeFace {
     pub fn get_vertices_template(&self) -> &'static [Vertex] {
@@ -251,16 +326,56 @@ impl CubeFace {
         &LOCAL_FACE_INDICES
     }
 
-    // pub fn all_faces() -> [CubeFace; 6] {
-    //     [
-    //         CubeFace::Front,
-    //         CubeFace::Back,
-    //         CubeFace::Right,
-    //         CubeFace::Left,
-    //         CubeFace::Top,
-    //         CubeFace::Bottom,
-    //     ]
-    // }
+    // Triangulates the face along the diagonal from vertex 1 to 3 instead of 0 to 2.
+    pub fn get_flipped_local_indices(&self) -> &'static [u16] {
+        &FLIPPED_LOCAL_FACE_INDICES
+    }
+
+    // Index into per-face arrays such as `Block::get_texture_atlas_indices`.
+    pub fn index(&self) -> usize {
+        match self {
+            CubeFace::Front => 0,
+            CubeFace::Back => 1,
+            CubeFace::Right => 2,
+            CubeFace::Left => 3,
+            CubeFace::Top => 4,
+            CubeFace::Bottom => 5,
+        }
+    }
+
+    pub fn all_faces() -> [CubeFace; 6] {
+        [
+            CubeFace::Front,
+            CubeFace::Back,
+            CubeFace::Right,
+            CubeFace::Left,
+            CubeFace::Top,
+            CubeFace::Bottom,
+        ]
+    }
+
+    // Unit vector pointing out of the face.
+    pub fn normal(&self) -> IVec3 {
+        match self {
+            CubeFace::Front => IVec3::NEG_Z,
+            CubeFace::Back => IVec3::Z,
+            CubeFace::Right => IVec3::X,
+            CubeFace::Left => IVec3::NEG_X,
+            CubeFace::Top => IVec3::Y,
+            CubeFace::Bottom => IVec3::NEG_Y,
+        }
+    }
+
+    pub fn opposite(&self) -> CubeFace {
+        match self {
+            CubeFace::Front => CubeFace::Back,
+            CubeFace::Back => CubeFace::Front,
+            CubeFace::Right => CubeFace::Left,
+            CubeFace::Left => CubeFace::Right,
+            CubeFace::Top => CubeFace::Bottom,
+            CubeFace::Bottom => CubeFace::Top,
+        }
+    }
 }
 
 // Later we will add functions to get texture coordinates too.
@@ -1,5 +1,9 @@
 // Corrected use statement based on glyph_brush re-exports
+use crate::biome::Biome;
 use crate::block::Block;
+use crate::chunk::ChunkMemoryUsage;
+use crate::mesher::MeshSettings;
+use crate::player::MovementMode;
 use glam::Vec3;
 use std::time::Instant;
 use wgpu::TextureFormat; // Import TextureFormat
@@ -22,6 +26,15 @@ pub struct DebugOverlay {
     frame_count: u32,
     accumulated_time: f32,
     fps: u32,
+    tick_count: u32,
+    tps: u32,
+    world_seed: Option<u64>,
+    // (render distance, loaded chunks, chunks being generated, chunks with GPU meshes)
+    chunk_stats: Option<(i32, usize, usize, usize)>,
+    chunk_memory: Option<ChunkMemoryUsage>,
+    mesh_settings: Option<MeshSettings>,
+    culling_stats: Option<(usize, usize, usize)>,
+    movement_mode: Option<MovementMode>,
 }
 
 impl DebugOverlay {
@@ -57,9 +70,62 @@ impl DebugOverlay {
             frame_count: 0,
             accumulated_time: 0.0,
             fps: 0,
+            tick_count: 0,
+            tps: 0,
+            world_seed: None,
+            chunk_stats: None,
+            chunk_memory: None,
+            mesh_settings: None,
+            culling_stats: None,
+            movement_mode: None,
         }
     }
 
+    // Simulation ticks run this frame, counted into the ticks per second shown next to FPS.
+    pub fn record_ticks(&mut self, ticks: u32) {
+        self.tick_count += ticks;
+    }
+
+    pub fn set_world_seed(&mut self, seed: u64) {
+        self.world_seed = Some(seed);
+    }
+
+    pub fn set_chunk_stats(
+        &mut self,
+        render_distance: i32,
+        loaded_chunks: usize,
+        generating_chunks: usize,
+        meshed_chunks: usize,
+    ) {
+        self.chunk_stats = Some((
+            render_distance,
+            loaded_chunks,
+            generating_chunks,
+            meshed_chunks,
+        ));
+    }
+
+    pub fn set_chunk_memory(&mut self, usage: ChunkMemoryUsage) {
+        self.chunk_memory = Some(usage);
+    }
+
+    pub fn set_culling_stats(
+        &mut self,
+        drawn_chunks: usize,
+        frustum_culled_chunks: usize,
+        cave_culled_chunks: usize,
+    ) {
+        self.culling_stats = Some((drawn_chunks, frustum_culled_chunks, cave_culled_chunks));
+    }
+
+    pub fn set_mesh_settings(&mut self, settings: MeshSettings) {
+        self.mesh_settings = Some(settings);
+    }
+
+    pub fn set_movement_mode(&mut self, mode: MovementMode) {
+        self.movement_mode = Some(mode);
+    }
+
     pub fn toggle_visibility(&mut self) {
         self.visible = !self.visible;
     }
@@ -71,13 +137,15 @@ impl DebugOverlay {
     pub fn update(
         &mut self,
         player_position: Vec3,
-        selected_block: Option<(glam::IVec3, &Block)>,
-        player_feet_block: Option<&Block>,
+        selected_block: Option<(glam::IVec3, Block)>,
+        player_feet_block: Option<Block>,
+        player_biome: Option<Biome>,
     ) {
         if !self.visible {
             // Reset FPS calculation when not visible to avoid large delta_time on re-enabling
             self.last_frame_time = Instant::now();
             self.frame_count = 0;
+            self.tick_count = 0;
             self.accumulated_time = 0.0;
             return;
         }
@@ -93,14 +161,71 @@ impl DebugOverlay {
         if self.accumulated_time >= 1.0 {
             self.fps = self.frame_count;
             self.frame_count = 0;
+            self.tps = self.tick_count;
+            self.tick_count = 0;
             self.accumulated_time -= 1.0;
         }
 
         let mut text_content = format!(
-            "FPS: {}\nx: {:.2}, y: {:.2}, z: {:.2}",
-            self.fps, player_position.x, player_position.y, player_position.z
+            "FPS: {}, TPS: {}\nx: {:.2}, y: {:.2}, z: {:.2}",
+            self.fps, self.tps, player_position.x, player_position.y, player_position.z
         );
 
+        if let Some(seed) = self.world_seed {
+            text_content.push_str(&format!("\nSeed: {}", seed));
+        }
+
+        if let Some((render_distance, loaded_chunks, generating_chunks, meshed_chunks)) =
+            self.chunk_stats
+        {
+            text_content.push_str(&format!(
+                "\nRender distance: {} (+/-), chunks: {} loaded, {} generating, {} meshed",
+                render_distance, loaded_chunks, generating_chunks, meshed_chunks
+            ));
+        }
+
+        if let Some((drawn_chunks, frustum_culled_chunks, cave_culled_chunks)) = self.culling_stats
+        {
+            text_content.push_str(&format!(
+                "\nChunks drawn: {}, culled: {} (frustum), {} (caves)",
+                drawn_chunks, frustum_culled_chunks, cave_culled_chunks
+            ));
+        }
+
+        if let Some(usage) = self.chunk_memory {
+            const MIB: f32 = 1024.0 * 1024.0;
+            text_content.push_str(&format!(
+                "\nChunk memory: {:.1} MiB in {} sections (blocks {:.1} MiB, light {:.1} MiB)",
+                usage.total_bytes() as f32 / MIB,
+                usage.sections,
+                usage.block_bytes as f32 / MIB,
+                usage.light_bytes as f32 / MIB
+            ));
+        }
+
+        if let Some(settings) = self.mesh_settings {
+            text_content.push_str(&format!(
+                "\nMesher: {} (F5), smooth lighting: {} (F4)",
+                settings.mesher.name(),
+                if settings.smooth_lighting {
+                    "on"
+                } else {
+                    "off"
+                }
+            ));
+        }
+
+        if let Some(mode) = self.movement_mode {
+            text_content.push_str(&format!(
+                "\nMovement: {} (double-tap Space: fly, F7: spectator)",
+                mode.name()
+            ));
+        }
+
+        if let Some(biome) = player_biome {
+            text_content.push_str(&format!("\nBiome: {}", biome.name()));
+        }
+
         if let Some(block) = player_feet_block {
             text_content.push_str(&format!(
                 "\nPlayer Light: {} sky, {} block",
@@ -111,8 +236,11 @@ impl DebugOverlay {
         if let Some((pos, block)) = selected_block {
             text_content.push_str("\n---\n");
             text_content.push_str(&format!(
-                "Looking at: {:?} at x: {}, y: {}, z: {}",
-                block.block_type, pos.x, pos.y, pos.z
+                "Looking at: {} at x: {}, y: {}, z: {}",
+                block.block_type.name(),
+                pos.x,
+                pos.y,
+                pos.z
             ));
         }
 
@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 #[derive(Debug, Default)]
 pub struct InputState {
     pub left_mouse_pressed_this_frame: bool,
@@ -59,3 +61,41 @@ impl InputState {
         self.right_mouse_released_this_frame = false;
     }
 }
+
+// How quickly a key has to be pressed again to count as a double tap.
+const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(300);
+
+// Recognises a key pressed twice in quick succession (e.g. double-tapping forward to sprint).
+#[derive(Debug, Default)]
+pub struct DoubleTap {
+    last_press: Option<Instant>,
+}
+
+impl DoubleTap {
+    // Records a key press and returns whether it completes a double tap. A third quick press
+    // starts a new pair instead of counting again.
+    pub fn press(&mut self, now: Instant) -> bool {
+        let is_double_tap = self
+            .last_press
+            .is_some_and(|last_press| now.duration_since(last_press) <= DOUBLE_TAP_WINDOW);
+        self.last_press = if is_double_tap { None } else { Some(now) };
+        is_double_tap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_tap_needs_two_quick_presses() {
+        let start = Instant::now();
+        let mut double_tap = DoubleTap::default();
+        assert!(!double_tap.press(start));
+        assert!(double_tap.press(start + Duration::from_millis(200)));
+        // A third press right after starts a new pair.
+        assert!(!double_tap.press(start + Duration::from_millis(250)));
+        // Too slow.
+        assert!(!double_tap.press(start + Duration::from_millis(900)));
+    }
+}
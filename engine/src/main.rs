@@ -1,17 +1,30 @@
+mod biome;
 mod block;
+mod block_registry;
 mod camera;
 mod chunk;
+mod chunk_manager;
+mod chunk_render;
+mod chunk_storage;
+mod chunk_workers;
 mod cube_geometry;
 mod debug_overlay;
 mod input;
+mod mesher;
 pub mod physics;
 pub mod player;
 mod raycast;
+mod save;
 mod texture;
+mod timestep;
 mod ui;
+mod visibility;
 mod wireframe_renderer;
 mod world;
+mod worldgen;
 
+use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 use wgpu::Trace;
 use winit::{
@@ -28,15 +41,20 @@ struct App {
     state: Option<State>,
     mouse_grabbed: bool,
     last_mouse_position: Option<winit::dpi::PhysicalPosition<f64>>,
+    world_dir: PathBuf,
+    // Only used when the world is created; a saved world keeps its own seed.
+    world_seed: Option<u64>,
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(world_dir: PathBuf, world_seed: Option<u64>) -> Self {
         Self {
             window: None,
             state: None,
             mouse_grabbed: false,
             last_mouse_position: None,
+            world_dir,
+            world_seed,
         }
     }
 
@@ -185,7 +203,12 @@ impl ApplicationHandler for App {
             let window_arc = Arc::new(event_loop.create_window(window_attributes).unwrap());
             self.window = Some(Arc::clone(&window_arc));
             let initial_size = window_arc.inner_size();
-            let state_val = pollster::block_on(State::new(Arc::clone(&window_arc), initial_size));
+            let state_val = pollster::block_on(State::new(
+                Arc::clone(&window_arc),
+                initial_size,
+                self.world_dir.clone(),
+                self.world_seed,
+            ));
             self.state = Some(state_val);
             self.set_mouse_grab(true);
         }
@@ -230,6 +253,9 @@ impl ApplicationHandler for App {
 
     fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
         println!("ApplicationHandler: Event loop is exiting. Cleaning up.");
+        if let Some(state) = self.state.as_mut() {
+            state.save_world();
+        }
         self.state = None;
     }
 }
@@ -238,10 +264,19 @@ impl ApplicationHandler for App {
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
     pub position: [f32; 3],
-    pub color: [f32; 3],
+    // Biome colour multiplied with the texture (white for untinted faces).
+    pub tint: [f32; 3],
+    // Texture coordinates in blocks. The shader wraps them into the `atlas_tile`, so a quad
+    // covering several blocks repeats its texture.
     pub uv: [f32; 2],
     pub tree_id: u32,
-    pub sky_light: u32,
+    // Light levels (0..15) and ambient occlusion (0 = fully occluded corner, 3 = open) are
+    // floats so the mesher's per-corner values blend across the face.
+    pub sky_light: f32,
+    pub block_light: f32,
+    pub ao: f32,
+    // Top-left corner of the face's texture in the atlas, in atlas UV units.
+    pub atlas_tile: [f32; 2],
 }
 
 impl Vertex {
@@ -277,7 +312,34 @@ impl Vertex {
                         + std::mem::size_of::<u32>())
                         as wgpu::BufferAddress,
                     shader_location: 4,
-                    format: wgpu::VertexFormat::Uint32,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>() * 2
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<u32>()
+                        + std::mem::size_of::<f32>())
+                        as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>() * 2
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<u32>()
+                        + std::mem::size_of::<f32>() * 2)
+                        as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>() * 2
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<u32>()
+                        + std::mem::size_of::<f32>() * 3)
+                        as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x2,
                 },
             ],
         }
@@ -285,34 +347,61 @@ impl Vertex {
 }
 
 use crate::block::BlockType;
-use crate::camera::CameraUniform;
-use crate::chunk::{CHUNK_DEPTH, CHUNK_HEIGHT, CHUNK_WIDTH};
-use crate::cube_geometry::CubeFace;
+use crate::block_registry::registry;
+use crate::camera::{CameraUniform, Frustum};
+use crate::chunk::{CHUNK_DEPTH, CHUNK_WIDTH, MAX_Y, MIN_Y, SECTION_COUNT};
+use crate::chunk_manager::{ChunkManager, DEFAULT_RENDER_DISTANCE};
+use crate::chunk_render::ChunkRenderData;
+use crate::chunk_workers::{ChunkWorkResult, ChunkWorkers};
 use crate::debug_overlay::DebugOverlay;
+use crate::mesher::{
+    ChunkMesh, ChunkNeighbourhood, MeshSettings, build_chunk_mesh, sort_back_to_front,
+};
 use crate::physics::AABB;
-use crate::physics::PLAYER_EYE_HEIGHT;
 use crate::player::Player;
 use crate::raycast::BlockFace;
+use crate::save::WorldSave;
+use crate::save::level::{self, LevelData, PlayerData};
+use crate::timestep::{FixedTimestep, TICK_DURATION};
 use crate::ui::item::{ItemStack, ItemType};
 use crate::ui::item_renderer::ItemRenderer;
 use crate::ui::ui_text::UIText;
+use crate::visibility::SectionVisibility;
 use crate::wireframe_renderer::WireframeRenderer;
 use crate::world::World;
 use glam::IVec3;
 use glam::Mat4;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 use wgpu_text::glyph_brush::{HorizontalAlign, Layout, OwnedSection, OwnedText, VerticalAlign};
 
-struct ChunkRenderBuffers {
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
-}
-
-struct ChunkRenderData {
-    opaque_buffers: Option<ChunkRenderBuffers>,
-    transparent_buffers: Option<ChunkRenderBuffers>,
-}
+// Finished worker meshes uploaded per frame; the rest wait so one frame never uploads a
+// whole ring of chunks at once.
+const MAX_MESH_UPLOADS_PER_FRAME: usize = 4;
+
+// Field of view, widened while sprinting and eased towards its target at FOV_EASE_RATE (1/s).
+const FOV_DEGREES: f32 = 45.0;
+const SPRINT_FOV_DEGREES: f32 = 52.0;
+const FOV_EASE_RATE: f32 = 10.0;
+
+// Game ticks between fluid steps (four per second), slow enough to watch water spread.
+const FLUID_TICK_INTERVAL: u64 = 15;
+
+// Number keys select the hotbar slots from left to right.
+const HOTBAR_KEYS: [KeyCode; ui::hotbar::NUM_SLOTS] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+// What a new world's hotbar starts with, from the first slot on.
+const STARTING_BLOCKS: [&str; 5] = ["dirt", "water", "torch", "glowstone", "lava"];
 
 struct State {
     surface: wgpu::Surface<'static>,
@@ -322,6 +411,7 @@ struct State {
     size: winit::dpi::PhysicalSize<u32>,
     render_pipeline: wgpu::RenderPipeline,
     transparent_render_pipeline: wgpu::RenderPipeline,
+    fluid_render_pipeline: wgpu::RenderPipeline,
     player: Player,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
@@ -329,6 +419,25 @@ struct State {
     world: World,
     chunk_render_data: HashMap<(i32, i32), ChunkRenderData>,
     active_chunk_coords: Vec<(i32, i32)>,
+    // The active chunks with geometry inside the view frustum and at least one section the
+    // cave-culling search reached, refreshed every update.
+    visible_chunk_coords: Vec<(i32, i32)>,
+    // Sections (chunk x, section index, chunk z) reached by the cave-culling search.
+    visible_sections: HashSet<IVec3>,
+    // Eye position the current frame is rendered from, set in `update`.
+    camera_position: glam::Vec3,
+    chunk_manager: ChunkManager,
+    chunk_workers: ChunkWorkers,
+    // Meshes built by the workers, waiting for their turn to be uploaded.
+    ready_meshes: HashMap<(i32, i32), ChunkMesh>,
+    mesh_settings: MeshSettings,
+    timestep: FixedTimestep,
+    forward_double_tap: input::DoubleTap,
+    jump_double_tap: input::DoubleTap,
+    fov_degrees: f32,
+    last_frame_time: Instant,
+    // Simulation ticks run since the world was opened.
+    game_tick: u64,
     depth_texture: wgpu::Texture,
     depth_texture_view: wgpu::TextureView,
     debug_overlay: DebugOverlay,
@@ -345,13 +454,12 @@ struct State {
     ui_text: UIText,
 }
 
-const ATLAS_COLS: f32 = 16.0;
-const ATLAS_ROWS: f32 = 1.0;
-
 impl State {
     async fn new(
         window_surface_target: Arc<Window>,
         initial_size: winit::dpi::PhysicalSize<u32>,
+        world_dir: PathBuf,
+        world_seed: Option<u64>,
     ) -> Self {
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
@@ -577,21 +685,121 @@ impl State {
                 cache: None,
             });
 
-        let initial_player_position = glam::Vec3::new(
-            CHUNK_WIDTH as f32 / 2.0,
-            (CHUNK_HEIGHT / 2) as f32 + 2.0,
-            CHUNK_DEPTH as f32 / 2.0,
-        );
-        let initial_yaw = -std::f32::consts::FRAC_PI_2;
-        let initial_pitch = 0.0;
+        // Fluid surfaces are blended over the scene behind them. They do not write depth, so
+        // fluid further back still shows through the surface in front of it.
+        let fluid_render_pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Fluid Render Pipeline"),
+                layout: Some(&render_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[Vertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_fluid"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: config.format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        let world_save = match WorldSave::open(&world_dir) {
+            Ok(save) => Some(Arc::new(save)),
+            Err(e) => {
+                eprintln!(
+                    "Failed to open world '{}', it will not be saved: {}",
+                    world_dir.display(),
+                    e
+                );
+                None
+            }
+        };
+        // Only a missing level file starts a new world in this directory. One that exists but
+        // will not load still belongs to the saved chunks, so the world runs without saving
+        // rather than generating next to them from another seed and overwriting it on exit.
+        let (world_save, level) = match world_save {
+            Some(save) => match save.load_level() {
+                Ok(level) => (Some(save), level),
+                Err(e) => {
+                    eprintln!(
+                        "Failed to load level data from '{}', the world will not be saved: {}",
+                        world_dir.display(),
+                        e
+                    );
+                    (None, None)
+                }
+            },
+            None => (None, None),
+        };
+        let world_seed = match &level {
+            Some(level) => {
+                if world_seed.is_some_and(|seed| seed != level.seed) {
+                    println!("Ignoring --seed, the saved world keeps its own seed.");
+                }
+                level.seed
+            }
+            None => world_seed.unwrap_or_else(rand::random),
+        };
+        println!("World seed: {}", world_seed);
+
+        let mut world = World::new(world_seed);
+        if let Some(save) = &world_save {
+            world = world.with_save(Arc::clone(save));
+        }
+
+        let (initial_player_position, initial_yaw, initial_pitch) = match &level {
+            Some(level) => (
+                glam::Vec3::from(level.player.position),
+                level.player.yaw,
+                level.player.pitch,
+            ),
+            None => {
+                let spawn_x = CHUNK_WIDTH as i32 / 2;
+                let spawn_z = CHUNK_DEPTH as i32 / 2;
+                let spawn_position = glam::Vec3::new(
+                    spawn_x as f32 + 0.5,
+                    world.surface_height(spawn_x, spawn_z) as f32 + 1.0,
+                    spawn_z as f32 + 0.5,
+                );
+                (spawn_position, -std::f32::consts::FRAC_PI_2, 0.0)
+            }
+        };
         let mouse_sensitivity = 0.003;
 
-        let player = Player::new(
+        let mut player = Player::new(
             initial_player_position,
             initial_yaw,
             initial_pitch,
             mouse_sensitivity,
         );
+        if let Some(level) = &level {
+            player.movement_mode = level.player.movement_mode;
+        }
 
         let camera_uniform = CameraUniform::new();
 
@@ -610,8 +818,6 @@ impl State {
             label: Some("camera_bind_group"),
         });
 
-        let world = World::new();
-
         let depth_texture_desc = wgpu::TextureDescriptor {
             size: wgpu::Extent3d {
                 width: config.width,
@@ -629,11 +835,28 @@ impl State {
         let depth_texture = device.create_texture(&depth_texture_desc);
         let depth_texture_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let debug_overlay = DebugOverlay::new(&device, &config);
+        let chunk_workers = ChunkWorkers::new(world.generator(), world.seed(), world.save());
+
+        let mut debug_overlay = DebugOverlay::new(&device, &config);
+        debug_overlay.set_world_seed(world.seed());
+        let mesh_settings = MeshSettings::default();
+        debug_overlay.set_mesh_settings(mesh_settings);
         let crosshair = ui::crosshair::Crosshair::new(&device, &config);
-        let inventory = ui::inventory::Inventory::new(&device, &config);
+        let mut inventory = ui::inventory::Inventory::new(&device, &config);
         let mut hotbar = ui::hotbar::Hotbar::new(&device, &config);
-        hotbar.items[0] = Some(ItemStack::new(ItemType::Block(BlockType::Dirt), 64));
+        match &level {
+            Some(level) => {
+                level::restore_slots(&level.inventory, &mut inventory.items);
+                level::restore_slots(&level.hotbar, &mut hotbar.items);
+            }
+            None => {
+                for (slot, name) in hotbar.items.iter_mut().zip(STARTING_BLOCKS) {
+                    *slot = registry()
+                        .by_name(name)
+                        .map(|block_type| ItemStack::new(ItemType::Block(block_type), 64));
+                }
+            }
+        }
 
         let ui_projection_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -662,7 +885,7 @@ impl State {
 
         let ui_text = UIText::new(&device, &config);
 
-        Self {
+        let state = Self {
             surface,
             device,
             queue,
@@ -670,6 +893,7 @@ impl State {
             size: initial_size,
             render_pipeline,
             transparent_render_pipeline,
+            fluid_render_pipeline,
             player,
             camera_uniform,
             camera_buffer,
@@ -677,6 +901,19 @@ impl State {
             world,
             chunk_render_data: HashMap::new(),
             active_chunk_coords: Vec::new(),
+            visible_chunk_coords: Vec::new(),
+            visible_sections: HashSet::new(),
+            camera_position: glam::Vec3::ZERO,
+            chunk_manager: ChunkManager::new(DEFAULT_RENDER_DISTANCE),
+            chunk_workers,
+            ready_meshes: HashMap::new(),
+            mesh_settings,
+            timestep: FixedTimestep::new(),
+            forward_double_tap: input::DoubleTap::default(),
+            jump_double_tap: input::DoubleTap::default(),
+            fov_degrees: FOV_DEGREES,
+            last_frame_time: Instant::now(),
+            game_tick: 0,
             depth_texture,
             depth_texture_view,
             debug_overlay,
@@ -691,361 +928,91 @@ impl State {
             item_renderer,
             dragged_item: None,
             ui_text,
+        };
+        // A new world records its seed before any of its chunks reach the disk.
+        if level.is_none()
+            && let Err(e) = state.save_level()
+        {
+            eprintln!("Failed to save level data: {}", e);
         }
+        state
     }
 
-    fn build_or_rebuild_chunk_mesh(&mut self, chunk_cx: i32, chunk_cz: i32) {
-        let mut opaque_vertices: Vec<Vertex> = Vec::new();
-        let mut opaque_indices: Vec<u16> = Vec::new();
-        let mut opaque_vertex_offset: u16 = 0;
-
-        let mut transparent_vertices: Vec<Vertex> = Vec::new();
-        let mut transparent_indices: Vec<u16> = Vec::new();
-        let mut transparent_vertex_offset: u16 = 0;
-
-        struct TransparentBlockData {
-            block: block::Block,
-            lx: usize,
-            ly: usize,
-            lz: usize,
-            world_center: glam::Vec3,
+    // Writes every loaded chunk, the structure blocks still waiting for unloaded chunks and
+    // the level file. Chunks unloaded earlier were already saved by `World::unload_chunk`.
+    fn save_world(&mut self) {
+        let Some(save) = self.world.save() else {
+            return;
+        };
+        if let Err(e) = self.world.save_all() {
+            eprintln!("Failed to save chunks: {}", e);
         }
-        let mut transparent_block_render_list: Vec<TransparentBlockData> = Vec::new();
+        match self.save_level() {
+            Ok(()) => println!("Saved world to '{}'", save.dir().display()),
+            Err(e) => eprintln!("Failed to save level data: {}", e),
+        }
+    }
 
-        let chunk_opt = self.world.get_chunk(chunk_cx, chunk_cz);
-        if chunk_opt.is_none() {
+    // Writes the seed, player and inventory to the level file. Also done when a world is
+    // created and whenever chunks are unloaded, so the region files written meanwhile never
+    // sit in a directory without the seed they were generated from.
+    fn save_level(&self) -> io::Result<()> {
+        let Some(save) = self.world.save() else {
+            return Ok(());
+        };
+        let level = LevelData {
+            version: level::LEVEL_FORMAT_VERSION,
+            seed: self.world.seed(),
+            player: PlayerData {
+                position: self.player.position.to_array(),
+                yaw: self.player.yaw,
+                pitch: self.player.pitch,
+                movement_mode: self.player.movement_mode,
+            },
+            inventory: level::save_slots(&self.inventory.items),
+            hotbar: level::save_slots(&self.hotbar.items),
+        };
+        save.save_level(&level)
+    }
+
+    // Meshes a chunk on the calling thread and uploads it right away. Used for block edits,
+    // where waiting a frame for the workers would be visible.
+    fn build_or_rebuild_chunk_mesh(&mut self, chunk_cx: i32, chunk_cz: i32) {
+        let Some(neighbourhood) = ChunkNeighbourhood::from_world(&self.world, chunk_cx, chunk_cz)
+        else {
             eprintln!(
                 "Attempted to build mesh for non-existent or non-generated chunk ({}, {})",
                 chunk_cx, chunk_cz
             );
             self.chunk_render_data.remove(&(chunk_cx, chunk_cz));
             return;
-        }
-        let chunk = chunk_opt.unwrap();
-
-        let chunk_world_origin_x = chunk_cx as f32 * CHUNK_WIDTH as f32;
-        let chunk_world_origin_z = chunk_cz as f32 * CHUNK_DEPTH as f32;
-
-        for lx in 0..CHUNK_WIDTH {
-            for ly in 0..CHUNK_HEIGHT {
-                for lz in 0..CHUNK_DEPTH {
-                    if let Some(block) = chunk.get_block(lx, ly, lz) {
-                        if block.block_type == BlockType::Air {
-                            continue;
-                        }
-                        let is_current_block_transparent = block.is_transparent();
-                        let default_block_color = match block.block_type {
-                            BlockType::Dirt => [0.5, 0.25, 0.05],
-                            BlockType::Grass => [0.0, 0.8, 0.1],
-                            BlockType::Bedrock => [0.5, 0.5, 0.5],
-                            BlockType::OakLog => [0.5, 0.5, 0.5],
-                            BlockType::OakLeaves => [0.5, 0.5, 0.5],
-                            BlockType::Air => unreachable!(),
-                        };
-                        let current_block_world_center = glam::Vec3::new(
-                            chunk_world_origin_x + lx as f32 + 0.5,
-                            ly as f32 + 0.5,
-                            chunk_world_origin_z + lz as f32 + 0.5,
-                        );
-                        let face_definitions: [(CubeFace, (i32, i32, i32)); 6] = [
-                            (CubeFace::Front, (0, 0, -1)),
-                            (CubeFace::Back, (0, 0, 1)),
-                            (CubeFace::Right, (1, 0, 0)),
-                            (CubeFace::Left, (-1, 0, 0)),
-                            (CubeFace::Top, (0, 1, 0)),
-                            (CubeFace::Bottom, (0, -1, 0)),
-                        ];
-                        for (face_type, offset) in face_definitions.iter() {
-                            let neighbor_world_bx =
-                                chunk_world_origin_x as i32 + lx as i32 + offset.0;
-                            let neighbor_world_by = ly as i32 + offset.1;
-                            let neighbor_world_bz =
-                                chunk_world_origin_z as i32 + lz as i32 + offset.2;
-
-                            let mut face_sky_light = 0;
-                            let mut is_face_visible = false;
-                            if neighbor_world_by >= 0 && neighbor_world_by < CHUNK_HEIGHT as i32 {
-                                if let Some(neighbor_block) = self.world.get_block_at_world(
-                                    neighbor_world_bx as f32,
-                                    neighbor_world_by as f32,
-                                    neighbor_world_bz as f32,
-                                ) {
-                                    if neighbor_block.is_transparent() {
-                                        is_face_visible = true;
-                                        face_sky_light = neighbor_block.sky_light;
-                                    }
-                                }
-                            } else {
-                                is_face_visible = true;
-                            }
-
-                            if is_face_visible {
-                                if !is_current_block_transparent {
-                                    let vertices_template = face_type.get_vertices_template();
-                                    let local_indices = face_type.get_local_indices();
-                                    let tex_size_x = 1.0 / ATLAS_COLS;
-                                    let tex_size_y = 1.0 / ATLAS_ROWS;
-                                    let all_face_atlas_indices = block.get_texture_atlas_indices();
-                                    let mut current_vertex_color = default_block_color;
-                                    let face_specific_atlas_indices: [f32; 2] = match face_type {
-                                        CubeFace::Front => all_face_atlas_indices[0],
-                                        CubeFace::Back => all_face_atlas_indices[1],
-                                        CubeFace::Right => all_face_atlas_indices[2],
-                                        CubeFace::Left => all_face_atlas_indices[3],
-                                        CubeFace::Top => all_face_atlas_indices[4],
-                                        CubeFace::Bottom => all_face_atlas_indices[5],
-                                    };
-                                    match block.block_type {
-                                        BlockType::Grass => {
-                                            if *face_type == CubeFace::Top {
-                                                current_vertex_color = [0.1, 0.9, 0.1];
-                                            } else if *face_type == CubeFace::Bottom {
-                                                current_vertex_color = [0.5, 0.25, 0.05];
-                                            } else {
-                                                current_vertex_color = [0.0, 0.8, 0.1];
-                                            }
-                                        }
-                                        _ => {}
-                                    }
-                                    let u_min = face_specific_atlas_indices[0] * tex_size_x;
-                                    let v_min = face_specific_atlas_indices[1] * tex_size_y;
-                                    let u_max = u_min + tex_size_x;
-                                    let v_max = v_min + tex_size_y;
-                                    let uvs_for_bl_br_tr_tl_order = [
-                                        [u_min, v_max],
-                                        [u_max, v_max],
-                                        [u_max, v_min],
-                                        [u_min, v_min],
-                                    ];
-                                    let uvs_for_bl_tl_tr_br_order = [
-                                        [u_min, v_max],
-                                        [u_min, v_min],
-                                        [u_max, v_min],
-                                        [u_max, v_max],
-                                    ];
-                                    let selected_face_uvs = match face_type {
-                                        CubeFace::Front
-                                        | CubeFace::Right
-                                        | CubeFace::Left
-                                        | CubeFace::Bottom => &uvs_for_bl_tl_tr_br_order,
-                                        CubeFace::Back | CubeFace::Top => {
-                                            &uvs_for_bl_br_tr_tl_order
-                                        }
-                                    };
-                                    for (i, v_template) in vertices_template.iter().enumerate() {
-                                        opaque_vertices.push(Vertex {
-                                            position: (current_block_world_center
-                                                + glam::Vec3::from(v_template.position))
-                                            .into(),
-                                            color: current_vertex_color,
-                                            uv: selected_face_uvs[i],
-                                            tree_id: 0,
-                                            sky_light: face_sky_light as u32,
-                                        });
-                                    }
-                                    for local_idx in local_indices {
-                                        opaque_indices.push(opaque_vertex_offset + local_idx);
-                                    }
-                                    opaque_vertex_offset += vertices_template.len() as u16;
-                                }
-                            }
-                        }
-                        if is_current_block_transparent {
-                            transparent_block_render_list.push(TransparentBlockData {
-                                block: *block,
-                                lx,
-                                ly,
-                                lz,
-                                world_center: current_block_world_center,
-                            });
-                        }
-                    }
-                }
-            }
-        }
-
-        for t_block_data in transparent_block_render_list {
-            let block = &t_block_data.block;
-            let _lx = t_block_data.lx;
-            let _ly = t_block_data.ly;
-            let _lz = t_block_data.lz;
-            let current_block_world_center = t_block_data.world_center;
-            let base_vertex_color = match block.block_type {
-                BlockType::OakLeaves => [0.1, 0.9, 0.2],
-                _ => [0.5, 0.5, 0.5],
-            };
-            let face_definitions: [(CubeFace, (i32, i32, i32)); 6] = [
-                (CubeFace::Front, (0, 0, -1)),
-                (CubeFace::Back, (0, 0, 1)),
-                (CubeFace::Right, (1, 0, 0)),
-                (CubeFace::Left, (-1, 0, 0)),
-                (CubeFace::Top, (0, 1, 0)),
-                (CubeFace::Bottom, (0, -1, 0)),
-            ];
-            for (face_type, _offset) in face_definitions.iter() {
-                let mut face_sky_light = 0;
-                let mut is_face_visible_for_transparent = false;
-                let neighbor_check_offset = match face_type {
-                    CubeFace::Front => (0, 0, -1),
-                    CubeFace::Back => (0, 0, 1),
-                    CubeFace::Right => (1, 0, 0),
-                    CubeFace::Left => (-1, 0, 0),
-                    CubeFace::Top => (0, 1, 0),
-                    CubeFace::Bottom => (0, -1, 0),
-                };
-                let neighbor_world_bx_transparent = (chunk_world_origin_x + t_block_data.lx as f32)
-                    as i32
-                    + neighbor_check_offset.0;
-                let neighbor_world_by_transparent =
-                    t_block_data.ly as i32 + neighbor_check_offset.1;
-                let neighbor_world_bz_transparent = (chunk_world_origin_z + t_block_data.lz as f32)
-                    as i32
-                    + neighbor_check_offset.2;
-
-                if neighbor_world_by_transparent >= 0
-                    && neighbor_world_by_transparent < CHUNK_HEIGHT as i32
-                {
-                    if let Some(neighbor_block_transparent) = self.world.get_block_at_world(
-                        neighbor_world_bx_transparent as f32,
-                        neighbor_world_by_transparent as f32,
-                        neighbor_world_bz_transparent as f32,
-                    ) {
-                        if neighbor_block_transparent.is_transparent()
-                            && neighbor_block_transparent.block_type != block.block_type
-                        {
-                            is_face_visible_for_transparent = true;
-                            face_sky_light = neighbor_block_transparent.sky_light;
-                        }
-                    }
-                } else {
-                    is_face_visible_for_transparent = true;
-                }
-
-                if !is_face_visible_for_transparent {
-                    continue;
-                }
+        };
+        // Any mesh still being built or waiting for upload is older than this one.
+        self.chunk_workers.cancel_mesh((chunk_cx, chunk_cz));
+        self.ready_meshes.remove(&(chunk_cx, chunk_cz));
+        let mesh = build_chunk_mesh(&neighbourhood, self.mesh_settings);
+        self.upload_chunk_mesh(chunk_cx, chunk_cz, mesh);
+    }
 
-                let vertices_template = face_type.get_vertices_template();
-                let local_indices = face_type.get_local_indices();
-                let tex_size_x = 1.0 / ATLAS_COLS;
-                let tex_size_y = 1.0 / ATLAS_ROWS;
-                let all_face_atlas_indices = block.get_texture_atlas_indices();
-                let current_vertex_color = base_vertex_color;
-                let face_specific_atlas_indices: [f32; 2] = match face_type {
-                    CubeFace::Front => all_face_atlas_indices[0],
-                    CubeFace::Back => all_face_atlas_indices[1],
-                    CubeFace::Right => all_face_atlas_indices[2],
-                    CubeFace::Left => all_face_atlas_indices[3],
-                    CubeFace::Top => all_face_atlas_indices[4],
-                    CubeFace::Bottom => all_face_atlas_indices[5],
-                };
-                let u_min = face_specific_atlas_indices[0] * tex_size_x;
-                let v_min = face_specific_atlas_indices[1] * tex_size_y;
-                let u_max = u_min + tex_size_x;
-                let v_max = v_min + tex_size_y;
-                let uvs_for_bl_br_tr_tl_order = [
-                    [u_min, v_max],
-                    [u_max, v_max],
-                    [u_max, v_min],
-                    [u_min, v_min],
-                ];
-                let uvs_for_bl_tl_tr_br_order = [
-                    [u_min, v_max],
-                    [u_min, v_min],
-                    [u_max, v_min],
-                    [u_max, v_max],
-                ];
-                let selected_face_uvs = match face_type {
-                    CubeFace::Front | CubeFace::Right | CubeFace::Left | CubeFace::Bottom => {
-                        &uvs_for_bl_tl_tr_br_order
-                    }
-                    CubeFace::Back | CubeFace::Top => &uvs_for_bl_br_tr_tl_order,
-                };
-                for (i, v_template) in vertices_template.iter().enumerate() {
-                    let current_tree_id = if block.block_type == BlockType::OakLeaves {
-                        block.tree_id.unwrap_or(0)
-                    } else {
-                        0
-                    };
-                    transparent_vertices.push(Vertex {
-                        position: (current_block_world_center
-                            + glam::Vec3::from(v_template.position))
-                        .into(),
-                        color: current_vertex_color,
-                        uv: selected_face_uvs[i],
-                        tree_id: current_tree_id,
-                        sky_light: face_sky_light as u32,
-                    });
-                }
-                for local_idx in local_indices {
-                    transparent_indices.push(transparent_vertex_offset + local_idx);
-                }
-                transparent_vertex_offset += vertices_template.len() as u16;
+    // Rebuilds every meshed chunk on the workers, e.g. after the mesh settings changed. The
+    // old meshes stay on screen until the new ones are uploaded.
+    fn remesh_all_chunks(&mut self) {
+        let mut coords: Vec<(i32, i32)> = self.chunk_render_data.keys().copied().collect();
+        coords.extend(self.ready_meshes.drain().map(|(coord, _)| coord));
+        for (cx, cz) in coords {
+            if let Some(neighbourhood) = ChunkNeighbourhood::from_world(&self.world, cx, cz) {
+                self.chunk_workers
+                    .request_mesh(neighbourhood, self.mesh_settings);
             }
         }
+    }
 
-        use wgpu::util::DeviceExt;
-        let mut opaque_buffers: Option<ChunkRenderBuffers> = None;
-        if !opaque_vertices.is_empty() && !opaque_indices.is_empty() {
-            let vertex_buffer = self
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some(&format!("Opaque Chunk VB ({}, {})", chunk_cx, chunk_cz)),
-                    contents: bytemuck::cast_slice(&opaque_vertices),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
-            let index_buffer = self
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some(&format!("Opaque Chunk IB ({}, {})", chunk_cx, chunk_cz)),
-                    contents: bytemuck::cast_slice(&opaque_indices),
-                    usage: wgpu::BufferUsages::INDEX,
-                });
-            opaque_buffers = Some(ChunkRenderBuffers {
-                vertex_buffer,
-                index_buffer,
-                num_indices: opaque_indices.len() as u32,
-            });
-        }
-        let mut transparent_buffers: Option<ChunkRenderBuffers> = None;
-        if !transparent_vertices.is_empty() && !transparent_indices.is_empty() {
-            let vertex_buffer = self
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some(&format!(
-                        "Transparent Chunk VB ({}, {})",
-                        chunk_cx, chunk_cz
-                    )),
-                    contents: bytemuck::cast_slice(&transparent_vertices),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
-            let index_buffer = self
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some(&format!(
-                        "Transparent Chunk IB ({}, {})",
-                        chunk_cx, chunk_cz
-                    )),
-                    contents: bytemuck::cast_slice(&transparent_indices),
-                    usage: wgpu::BufferUsages::INDEX,
-                });
-            transparent_buffers = Some(ChunkRenderBuffers {
-                vertex_buffer,
-                index_buffer,
-                num_indices: transparent_indices.len() as u32,
-            });
-        }
-        if opaque_buffers.is_some() || transparent_buffers.is_some() {
-            self.chunk_render_data.insert(
-                (chunk_cx, chunk_cz),
-                ChunkRenderData {
-                    opaque_buffers,
-                    transparent_buffers,
-                },
-            );
-        } else {
-            self.chunk_render_data.remove(&(chunk_cx, chunk_cz));
-        }
+    fn upload_chunk_mesh(&mut self, chunk_cx: i32, chunk_cz: i32, mesh: ChunkMesh) {
+        // Kept even when both are empty (e.g. an all-air chunk) so it is not meshed again.
+        self.chunk_render_data.insert(
+            (chunk_cx, chunk_cz),
+            ChunkRenderData::upload(&self.device, (chunk_cx, chunk_cz), &mesh),
+        );
     }
 
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
@@ -1097,6 +1064,7 @@ impl State {
                     KeyEvent {
                         physical_key: PhysicalKey::Code(key_code),
                         state,
+                        repeat,
                         ..
                     },
                 ..
@@ -1104,6 +1072,9 @@ impl State {
                 let is_pressed = *state == ElementState::Pressed;
                 match key_code {
                     KeyCode::KeyW | KeyCode::ArrowUp => {
+                        if is_pressed && !repeat && self.forward_double_tap.press(Instant::now()) {
+                            self.player.sprinting = true;
+                        }
                         self.player.movement_intention.forward = is_pressed;
                         true
                     }
@@ -1120,10 +1091,20 @@ impl State {
                         true
                     }
                     KeyCode::Space => {
+                        if is_pressed && !repeat && self.jump_double_tap.press(Instant::now()) {
+                            self.player.toggle_flying();
+                        }
                         self.player.movement_intention.jump = is_pressed;
                         true
                     }
-                    KeyCode::ShiftLeft | KeyCode::ShiftRight => false,
+                    KeyCode::ControlLeft | KeyCode::ControlRight => {
+                        self.player.movement_intention.sprint = is_pressed;
+                        true
+                    }
+                    KeyCode::ShiftLeft | KeyCode::ShiftRight => {
+                        self.player.movement_intention.sneak = is_pressed;
+                        true
+                    }
                     KeyCode::Escape => false,
                     KeyCode::F3 => {
                         if is_pressed {
@@ -1131,6 +1112,50 @@ impl State {
                         }
                         true
                     }
+                    KeyCode::F4 => {
+                        if is_pressed {
+                            self.mesh_settings.smooth_lighting =
+                                !self.mesh_settings.smooth_lighting;
+                            self.debug_overlay.set_mesh_settings(self.mesh_settings);
+                            self.remesh_all_chunks();
+                        }
+                        true
+                    }
+                    KeyCode::F5 => {
+                        if is_pressed {
+                            self.mesh_settings.mesher = self.mesh_settings.mesher.next();
+                            self.debug_overlay.set_mesh_settings(self.mesh_settings);
+                            self.remesh_all_chunks();
+                        }
+                        true
+                    }
+                    KeyCode::F7 => {
+                        if is_pressed {
+                            self.player.toggle_spectator();
+                        }
+                        true
+                    }
+                    _ if HOTBAR_KEYS.contains(key_code) => {
+                        if is_pressed {
+                            let slot = HOTBAR_KEYS.iter().position(|key| key == key_code);
+                            self.hotbar.select_slot(slot.unwrap());
+                        }
+                        true
+                    }
+                    KeyCode::Equal | KeyCode::NumpadAdd => {
+                        if is_pressed {
+                            let render_distance = self.chunk_manager.render_distance();
+                            self.chunk_manager.set_render_distance(render_distance + 1);
+                        }
+                        true
+                    }
+                    KeyCode::Minus | KeyCode::NumpadSubtract => {
+                        if is_pressed {
+                            let render_distance = self.chunk_manager.render_distance();
+                            self.chunk_manager.set_render_distance(render_distance - 1);
+                        }
+                        true
+                    }
                     _ => false,
                 }
             }
@@ -1138,44 +1163,138 @@ impl State {
         }
     }
 
-    fn update(&mut self) {
-        if self.inventory_open {
-            self.handle_inventory_interaction();
-        } else {
-            self.handle_block_interactions();
+    // Keeps the world and the GPU meshes in step with the player's position: unloads distant
+    // chunks, queues generation and meshing on the workers and uploads finished meshes.
+    fn stream_chunks(&mut self, center_chunk: (i32, i32)) {
+        let mut unloaded_any = false;
+        for coord in self
+            .chunk_manager
+            .chunks_to_unload(center_chunk, self.world.loaded_chunk_coords())
+        {
+            self.world.unload_chunk(coord.0, coord.1);
+            self.chunk_render_data.remove(&coord);
+            self.ready_meshes.remove(&coord);
+            self.chunk_workers.cancel(coord);
+            unloaded_any = true;
         }
-        let dt_secs = 1.0 / 60.0;
-        let player_pos = self.player.position;
-        let current_chunk_x = (player_pos.x / CHUNK_WIDTH as f32).floor() as i32;
-        let current_chunk_z = (player_pos.z / CHUNK_DEPTH as f32).floor() as i32;
-        let mut new_active_chunk_coords = Vec::new();
-        let render_distance = 1;
-        for dx in -render_distance..=render_distance {
-            for dz in -render_distance..=render_distance {
-                let target_cx = current_chunk_x + dx;
-                let target_cz = current_chunk_z + dz;
-                new_active_chunk_coords.push((target_cx, target_cz));
-                let _ = self.world.get_or_create_chunk(target_cx, target_cz);
-            }
+        if unloaded_any && let Err(e) = self.save_level() {
+            eprintln!("Failed to save level data: {}", e);
         }
-        self.active_chunk_coords = new_active_chunk_coords;
+        // Generation requests for chunks that fell out of range before they were done.
+        let chunk_manager = &self.chunk_manager;
+        self.chunk_workers.retain_generations(|coord| {
+            chunk_manager.is_within_unload_distance(center_chunk, coord)
+        });
 
         let mut coords_to_mesh: Vec<(i32, i32)> = Vec::new();
-        for &(cx, cz) in &self.active_chunk_coords {
-            if !self.chunk_render_data.contains_key(&(cx, cz)) {
-                coords_to_mesh.push((cx, cz));
+        for result in self.chunk_workers.poll() {
+            match result {
+                ChunkWorkResult::Generated { chunk, placements } => {
+                    let (cx, cz) = chunk.coord;
+                    if self.world.insert_chunk(chunk, placements) {
+                        // Faces on the neighbours' borders were hidden while this chunk was missing.
+                        for neighbour in [(cx + 1, cz), (cx - 1, cz), (cx, cz + 1), (cx, cz - 1)] {
+                            if self.chunk_render_data.contains_key(&neighbour) {
+                                coords_to_mesh.push(neighbour);
+                            }
+                        }
+                    }
+                }
+                ChunkWorkResult::Meshed { coord, mesh } => {
+                    if self.world.get_chunk(coord.0, coord.1).is_some() {
+                        self.ready_meshes.insert(coord, mesh);
+                    }
+                }
+            }
+        }
+
+        let world = &self.world;
+        let chunk_workers = &self.chunk_workers;
+        let chunks_to_load = self.chunk_manager.chunks_to_load(center_chunk, |(cx, cz)| {
+            world.get_chunk(cx, cz).is_some() || chunk_workers.is_generating((cx, cz))
+        });
+        for coord in chunks_to_load {
+            self.chunk_workers.request_generation(coord);
+        }
+        self.active_chunk_coords = self.chunk_manager.chunks_in_range(center_chunk);
+
+        for &coord in &self.active_chunk_coords {
+            if !self.chunk_render_data.contains_key(&coord)
+                && !self.ready_meshes.contains_key(&coord)
+                && !self.chunk_workers.is_meshing(coord)
+                && self.world.get_chunk(coord.0, coord.1).is_some()
+            {
+                coords_to_mesh.push(coord);
+            }
+        }
+        // Neighbours that received blocks from a newly generated chunk's structures or that
+        // a block edit reached, and chunks that fluid flowed through.
+        for coord in self.world.take_dirty_chunks() {
+            if self.chunk_render_data.contains_key(&coord) {
+                coords_to_mesh.push(coord);
             }
         }
         coords_to_mesh.sort_unstable();
         coords_to_mesh.dedup();
-        for (cx, cz) in &coords_to_mesh {
-            self.build_or_rebuild_chunk_mesh(*cx, *cz);
+        for (cx, cz) in coords_to_mesh {
+            if let Some(neighbourhood) = ChunkNeighbourhood::from_world(&self.world, cx, cz) {
+                self.chunk_workers
+                    .request_mesh(neighbourhood, self.mesh_settings);
+            }
         }
 
-        let mut selected_block_data = None;
+        // Upload the finished meshes closest to the player first, a few per frame.
+        let mut ready: Vec<(i32, i32)> = self.ready_meshes.keys().copied().collect();
+        ready.sort_by_key(|&(cx, cz)| {
+            let (dx, dz) = (cx - center_chunk.0, cz - center_chunk.1);
+            dx * dx + dz * dz
+        });
+        for coord in ready.into_iter().take(MAX_MESH_UPLOADS_PER_FRAME) {
+            if let Some(mesh) = self.ready_meshes.remove(&coord) {
+                self.upload_chunk_mesh(coord.0, coord.1, mesh);
+            }
+        }
+    }
+
+    // One fixed simulation step: block interaction, world ticks and player physics.
+    fn tick(&mut self) {
+        if self.inventory_open {
+            self.handle_inventory_interaction();
+        } else {
+            self.handle_block_interactions();
+        }
+        // Clicks belong to the tick that handled them; a frame without a tick keeps them for
+        // the next one.
+        self.input_state.clear_frame_state();
+
+        self.game_tick += 1;
+        if self.game_tick.is_multiple_of(FLUID_TICK_INTERVAL) {
+            self.world.tick_fluids();
+        }
         if !self.inventory_open {
             self.player
-                .update_physics_and_collision(dt_secs, &self.world);
+                .update_physics_and_collision(TICK_DURATION.as_secs_f32(), &self.world);
+        }
+    }
+
+    fn update(&mut self) {
+        let now = Instant::now();
+        let frame_time = now - self.last_frame_time;
+        let ticks = self.timestep.advance(frame_time);
+        self.last_frame_time = now;
+        for _ in 0..ticks {
+            self.tick();
+        }
+        self.debug_overlay.record_ticks(ticks);
+
+        let player_pos = self.player.position;
+        let current_chunk_x = (player_pos.x / CHUNK_WIDTH as f32).floor() as i32;
+        let current_chunk_z = (player_pos.z / CHUNK_DEPTH as f32).floor() as i32;
+        let center_chunk = (current_chunk_x, current_chunk_z);
+        self.stream_chunks(center_chunk);
+
+        let mut selected_block_data = None;
+        if !self.inventory_open {
             const RAYCAST_MAX_DISTANCE: f32 = 5.0;
             self.selected_block =
                 crate::raycast::cast_ray(&self.player, &self.world, RAYCAST_MAX_DISTANCE);
@@ -1195,7 +1314,10 @@ impl State {
             self.wireframe_renderer.update_selection(None);
         }
 
-        let camera_eye = self.player.position + glam::Vec3::new(0.0, PLAYER_EYE_HEIGHT, 0.0);
+        // The camera follows the player smoothly between ticks rather than jumping once per
+        // tick.
+        let camera_eye = self.player.interpolated_eye_position(self.timestep.alpha());
+        self.camera_position = camera_eye;
         let camera_front = glam::Vec3::new(
             self.player.yaw.cos() * self.player.pitch.cos(),
             self.player.pitch.sin(),
@@ -1205,7 +1327,14 @@ impl State {
         let camera_target = camera_eye + camera_front;
         let view_matrix = Mat4::look_at_rh(camera_eye, camera_target, glam::Vec3::Y);
         let aspect_ratio = self.config.width as f32 / self.config.height as f32;
-        let fovy_radians = 45.0f32.to_radians();
+        let target_fov_degrees = if self.player.sprinting {
+            SPRINT_FOV_DEGREES
+        } else {
+            FOV_DEGREES
+        };
+        let fov_ease = 1.0 - (-FOV_EASE_RATE * frame_time.as_secs_f32()).exp();
+        self.fov_degrees += (target_fov_degrees - self.fov_degrees) * fov_ease;
+        let fovy_radians = self.fov_degrees.to_radians();
         let znear = 0.1;
         let zfar = 1000.0;
         let projection_matrix = Mat4::perspective_rh(fovy_radians, aspect_ratio, znear, zfar);
@@ -1217,15 +1346,88 @@ impl State {
             bytemuck::cast_slice(&[self.camera_uniform]),
         );
 
+        let frustum = Frustum::from_view_proj(view_proj_matrix);
+        let active_chunks: HashSet<(i32, i32)> = self.active_chunk_coords.iter().copied().collect();
+        let chunk_render_data = &self.chunk_render_data;
+        let visibility_at = |section: IVec3| {
+            let coord = (section.x, section.z);
+            if !(0..SECTION_COUNT as i32).contains(&section.y) || !active_chunks.contains(&coord) {
+                return None;
+            }
+            // Active chunks that are not meshed yet are treated as open air.
+            let visibility = chunk_render_data
+                .get(&coord)
+                .and_then(|chunk_data| chunk_data.section_visibility.get(section.y as usize))
+                .copied()
+                .unwrap_or(SectionVisibility::ALL);
+            Some(visibility)
+        };
+        self.visible_sections = visibility::visible_sections(
+            visibility::section_containing(camera_eye),
+            visibility_at,
+            |section| frustum.intersects_aabb(&visibility::section_bounds(section)),
+        );
+
+        self.visible_chunk_coords.clear();
+        let mut frustum_culled_chunks = 0;
+        let mut cave_culled_chunks = 0;
+        for coord in &self.active_chunk_coords {
+            let Some(bounds) = self
+                .chunk_render_data
+                .get(coord)
+                .and_then(|chunk_data| chunk_data.bounds)
+            else {
+                continue;
+            };
+            let any_section_visible = (0..SECTION_COUNT as i32).any(|section| {
+                self.visible_sections
+                    .contains(&IVec3::new(coord.0, section, coord.1))
+            });
+            if !frustum.intersects_aabb(&bounds) {
+                frustum_culled_chunks += 1;
+            } else if any_section_visible {
+                self.visible_chunk_coords.push(*coord);
+            } else {
+                cave_culled_chunks += 1;
+            }
+        }
+        self.debug_overlay.set_culling_stats(
+            self.visible_chunk_coords.len(),
+            frustum_culled_chunks,
+            cave_culled_chunks,
+        );
+        for coord in &self.visible_chunk_coords {
+            if let Some(chunk_data) = self.chunk_render_data.get_mut(coord) {
+                chunk_data.sort_transparent(&self.queue, camera_eye);
+            }
+        }
+
         let player_feet_block = self.world.get_block_at_world(
             self.player.position.x,
             self.player.position.y,
             self.player.position.z,
         );
 
+        let player_biome = self
+            .world
+            .biome_at_world(self.player.position.x, self.player.position.z);
+
+        self.debug_overlay.set_chunk_stats(
+            self.chunk_manager.render_distance(),
+            self.world.loaded_chunk_count(),
+            self.chunk_workers.pending_generation_count(),
+            self.chunk_render_data.len(),
+        );
         self.debug_overlay
-            .update(self.player.position, selected_block_data, player_feet_block);
-        self.input_state.clear_frame_state();
+            .set_chunk_memory(self.world.chunk_memory_usage());
+        self.debug_overlay
+            .set_movement_mode(self.player.movement_mode);
+        self.debug_overlay.update(
+            self.player.position,
+            selected_block_data,
+            player_feet_block,
+            player_biome,
+        );
     }
 
 
@@ -1239,14 +1441,11 @@ impl State {
     fn handle_block_interactions(&mut self) {
         if self.input_state.left_mouse_pressed_this_frame {
             if let Some((block_pos, _face)) = self.selected_block {
-                match self.world.set_block(block_pos, BlockType::Air) {
+                match self.world.set_block(block_pos, BlockType::AIR) {
                     Ok(chunk_coord) => {
+                        // Neighbours the edit reached are marked dirty and left to the workers.
                         self.chunk_render_data.remove(&chunk_coord);
                         self.build_or_rebuild_chunk_mesh(chunk_coord.0, chunk_coord.1);
-                        self.build_or_rebuild_chunk_mesh(chunk_coord.0 + 1, chunk_coord.1);
-                        self.build_or_rebuild_chunk_mesh(chunk_coord.0 - 1, chunk_coord.1);
-                        self.build_or_rebuild_chunk_mesh(chunk_coord.0, chunk_coord.1 + 1);
-                        self.build_or_rebuild_chunk_mesh(chunk_coord.0, chunk_coord.1 - 1);
                     }
                     Err(e) => {
                         eprintln!("Error removing block: {}", e);
@@ -1271,15 +1470,14 @@ impl State {
                     min: new_block_pos.as_vec3(),
                     max: new_block_pos.as_vec3() + glam::Vec3::ONE,
                 };
-                if !player_aabb.intersects(&new_block_aabb) {
-                    match self.world.set_block(new_block_pos, BlockType::Grass) {
+                if let Some(placed_block) = self.hotbar.selected_block()
+                    && !player_aabb.intersects(&new_block_aabb)
+                {
+                    match self.world.set_block(new_block_pos, placed_block) {
                         Ok(chunk_coord) => {
+                            // Neighbours the edit reached are marked dirty and left to the workers.
                             self.chunk_render_data.remove(&chunk_coord);
                             self.build_or_rebuild_chunk_mesh(chunk_coord.0, chunk_coord.1);
-                            self.build_or_rebuild_chunk_mesh(chunk_coord.0 + 1, chunk_coord.1);
-                            self.build_or_rebuild_chunk_mesh(chunk_coord.0 - 1, chunk_coord.1);
-                            self.build_or_rebuild_chunk_mesh(chunk_coord.0, chunk_coord.1 + 1);
-                            self.build_or_rebuild_chunk_mesh(chunk_coord.0, chunk_coord.1 - 1);
                         }
                         Err(e) => {
                             eprintln!("Error placing block: {}", e);
@@ -1333,78 +1531,48 @@ impl State {
             render_pass.set_pipeline(&self.render_pipeline);
             render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
             render_pass.set_bind_group(1, &self.block_atlas_bind_group, &[]);
-            for chunk_coord in &self.active_chunk_coords {
+            for chunk_coord in &self.visible_chunk_coords {
                 if let Some(chunk_data) = self.chunk_render_data.get(chunk_coord) {
-                    if let Some(ref opaque_buffers) = chunk_data.opaque_buffers {
-                        if opaque_buffers.num_indices > 0 {
-                            render_pass
-                                .set_vertex_buffer(0, opaque_buffers.vertex_buffer.slice(..));
-                            render_pass.set_index_buffer(
-                                opaque_buffers.index_buffer.slice(..),
-                                wgpu::IndexFormat::Uint16,
-                            );
-                            render_pass.draw_indexed(0..opaque_buffers.num_indices, 0, 0..1);
-                        }
-                    }
+                    chunk_data.draw_opaque_sections(&mut render_pass, |section| {
+                        self.visible_sections.contains(&IVec3::new(
+                            chunk_coord.0,
+                            section as i32,
+                            chunk_coord.1,
+                        ))
+                    });
                 }
             }
             self.wireframe_renderer
                 .draw(&mut render_pass, &self.queue, &self.world);
             render_pass.set_pipeline(&self.transparent_render_pipeline);
             render_pass.set_bind_group(1, &self.block_atlas_bind_group, &[]);
-            let mut sorted_transparent_chunks = self.active_chunk_coords.clone();
-            let player_pos = self.player.position;
-            sorted_transparent_chunks.sort_by(|a, b| {
-                let pos_a = glam::Vec3::new(
-                    (a.0 as f32 + 0.5) * CHUNK_WIDTH as f32,
-                    CHUNK_HEIGHT as f32 / 2.0,
-                    (a.1 as f32 + 0.5) * CHUNK_DEPTH as f32,
-                );
-                let pos_b = glam::Vec3::new(
-                    (b.0 as f32 + 0.5) * CHUNK_WIDTH as f32,
-                    CHUNK_HEIGHT as f32 / 2.0,
-                    (b.1 as f32 + 0.5) * CHUNK_DEPTH as f32,
-                );
-                let dist_a = player_pos.distance_squared(pos_a);
-                let dist_b = player_pos.distance_squared(pos_b);
-                dist_b
-                    .partial_cmp(&dist_a)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
-            let mut sorted_transparent_chunks = self.active_chunk_coords.clone();
-            let player_pos = self.player.position;
-            sorted_transparent_chunks.sort_by(|a, b| {
-                let pos_a = glam::Vec3::new(
-                    (a.0 as f32 + 0.5) * CHUNK_WIDTH as f32,
-                    CHUNK_HEIGHT as f32 / 2.0,
-                    (a.1 as f32 + 0.5) * CHUNK_DEPTH as f32,
-                );
-                let pos_b = glam::Vec3::new(
-                    (b.0 as f32 + 0.5) * CHUNK_WIDTH as f32,
-                    CHUNK_HEIGHT as f32 / 2.0,
-                    (b.1 as f32 + 0.5) * CHUNK_DEPTH as f32,
-                );
-                let dist_a = player_pos.distance_squared(pos_a);
-                let dist_b = player_pos.distance_squared(pos_b);
-                dist_b
-                    .partial_cmp(&dist_a)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
+            let mut sorted_transparent_chunks = self.visible_chunk_coords.clone();
+            sort_back_to_front(
+                &mut sorted_transparent_chunks,
+                self.camera_position,
+                |coord| {
+                    glam::Vec3::new(
+                        (coord.0 as f32 + 0.5) * CHUNK_WIDTH as f32,
+                        (MIN_Y + MAX_Y) as f32 / 2.0,
+                        (coord.1 as f32 + 0.5) * CHUNK_DEPTH as f32,
+                    )
+                },
+            );
             for chunk_coord in &sorted_transparent_chunks {
                 if let Some(chunk_data) = self.chunk_render_data.get(chunk_coord) {
                     if let Some(ref transparent_buffers) = chunk_data.transparent_buffers {
-                        if transparent_buffers.num_indices > 0 {
-                            render_pass
-                                .set_vertex_buffer(0, transparent_buffers.vertex_buffer.slice(..));
-                            render_pass.set_index_buffer(
-                                transparent_buffers.index_buffer.slice(..),
-                                wgpu::IndexFormat::Uint16,
-                            );
-                            render_pass.draw_indexed(0..transparent_buffers.num_indices, 0, 0..1);
-                        }
+                        transparent_buffers.draw(&mut render_pass);
                     }
                 }
             }
+            render_pass.set_pipeline(&self.fluid_render_pipeline);
+            for chunk_coord in &sorted_transparent_chunks {
+                if let Some(chunk_data) = self.chunk_render_data.get(chunk_coord)
+                    && let Some(ref fluid_buffers) = chunk_data.fluid_buffers
+                {
+                    fluid_buffers.draw(&mut render_pass);
+                }
+            }
         }
         {
             let mut ui_render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -1549,10 +1717,33 @@ impl State {
     }
 }
 
+const DEFAULT_WORLD_DIR: &str = "saves/world";
+
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+// None if no valid --seed was given; a new world then picks a random one.
+fn world_seed_from_args() -> Option<u64> {
+    let value = arg_value("--seed")?;
+    value
+        .parse()
+        .map_err(|_| eprintln!("Invalid --seed value '{}', using a random seed.", value))
+        .ok()
+}
+
+fn world_dir_from_args() -> PathBuf {
+    PathBuf::from(arg_value("--world").unwrap_or_else(|| DEFAULT_WORLD_DIR.to_string()))
+}
+
 pub async fn run() {
     env_logger::init();
     let event_loop = EventLoop::new().unwrap();
-    let mut app = App::new();
+    let mut app = App::new(world_dir_from_args(), world_seed_from_args());
     event_loop.run_app(&mut app).unwrap();
 }
 
@@ -0,0 +1,1065 @@
+// CPU side of chunk meshing. Works on an immutable snapshot of a chunk and its neighbours,
+// so it can run on a worker thread; `State` only uploads the resulting buffers.
+
+use crate::Vertex;
+use crate::block::{Block, BlockType};
+use crate::chunk::{
+    CHUNK_DEPTH, CHUNK_WIDTH, Chunk, MAX_Y, MIN_Y, SECTION_COUNT, SECTION_SIZE, local_y_to_world,
+};
+use crate::cube_geometry::CubeFace;
+use crate::visibility::{SectionVisibility, chunk_visibility};
+use crate::world::World;
+use glam::{IVec3, Vec3};
+use std::ops::Range;
+use std::sync::Arc;
+
+const ATLAS_COLS: f32 = 16.0;
+const ATLAS_ROWS: f32 = 1.0;
+
+// Every face with the direction it points in.
+const FACES: [(CubeFace, IVec3); 6] = [
+    (CubeFace::Front, IVec3::new(0, 0, -1)),
+    (CubeFace::Back, IVec3::new(0, 0, 1)),
+    (CubeFace::Right, IVec3::new(1, 0, 0)),
+    (CubeFace::Left, IVec3::new(-1, 0, 0)),
+    (CubeFace::Top, IVec3::new(0, 1, 0)),
+    (CubeFace::Bottom, IVec3::new(0, -1, 0)),
+];
+
+// The greedy mesher works on one section-sized slice at a time.
+const _: () = assert!(CHUNK_WIDTH == SECTION_SIZE && CHUNK_DEPTH == SECTION_SIZE);
+
+// A chunk plus the eight chunks around it, as far as they are loaded. Faces on the border
+// towards a missing neighbour are hidden until the neighbour arrives; the diagonal chunks
+// are only sampled for smooth lighting at the chunk's corners.
+pub struct ChunkNeighbourhood {
+    pub center: Arc<Chunk>,
+    // [+X, -X, +Z, -Z, +X+Z, +X-Z, -X+Z, -X-Z]
+    pub neighbours: [Option<Arc<Chunk>>; 8],
+}
+
+impl ChunkNeighbourhood {
+    pub fn from_world(world: &World, chunk_x: i32, chunk_z: i32) -> Option<Self> {
+        Some(ChunkNeighbourhood {
+            center: world.chunk_snapshot(chunk_x, chunk_z)?,
+            neighbours: [
+                world.chunk_snapshot(chunk_x + 1, chunk_z),
+                world.chunk_snapshot(chunk_x - 1, chunk_z),
+                world.chunk_snapshot(chunk_x, chunk_z + 1),
+                world.chunk_snapshot(chunk_x, chunk_z - 1),
+                world.chunk_snapshot(chunk_x + 1, chunk_z + 1),
+                world.chunk_snapshot(chunk_x + 1, chunk_z - 1),
+                world.chunk_snapshot(chunk_x - 1, chunk_z + 1),
+                world.chunk_snapshot(chunk_x - 1, chunk_z - 1),
+            ],
+        })
+    }
+
+    // Same contract as `World::get_block_at_world`, limited to this neighbourhood.
+    pub fn get_block_at_world(&self, world_x: f32, world_y: f32, world_z: f32) -> Option<Block> {
+        let ((chunk_x, chunk_z), (local_x, local_y, local_z)) =
+            World::world_to_chunk_coords(world_x, world_y, world_z)?;
+        let (center_x, center_z) = self.center.coord;
+        let chunk = match (chunk_x - center_x, chunk_z - center_z) {
+            (0, 0) => Some(&self.center),
+            (1, 0) => self.neighbours[0].as_ref(),
+            (-1, 0) => self.neighbours[1].as_ref(),
+            (0, 1) => self.neighbours[2].as_ref(),
+            (0, -1) => self.neighbours[3].as_ref(),
+            (1, 1) => self.neighbours[4].as_ref(),
+            (1, -1) => self.neighbours[5].as_ref(),
+            (-1, 1) => self.neighbours[6].as_ref(),
+            (-1, -1) => self.neighbours[7].as_ref(),
+            _ => None,
+        }?;
+        chunk.get_block(local_x, local_y, local_z)
+    }
+
+    // Light and opacity of the cell at a block position, for smooth lighting. Everything
+    // above the world is open sky; cells below it or in missing chunks are unknown.
+    fn light_sample(&self, pos: IVec3) -> Option<LightSample> {
+        if pos.y >= MAX_Y {
+            return Some(LightSample {
+                occludes: false,
+                sky_light: 15,
+                block_light: 0,
+            });
+        }
+        let block = self.get_block_at_world(pos.x as f32, pos.y as f32, pos.z as f32)?;
+        Some(LightSample {
+            occludes: !block.is_transparent(),
+            sky_light: block.sky_light,
+            block_light: block.block_light,
+        })
+    }
+}
+
+// Options that change the generated geometry; changing one means remeshing every chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeshSettings {
+    // Per-corner light averaged over the surrounding blocks, plus ambient occlusion. When
+    // off, every face is lit evenly by the block in front of it.
+    pub smooth_lighting: bool,
+    pub mesher: MesherKind,
+}
+
+impl Default for MeshSettings {
+    fn default() -> Self {
+        MeshSettings {
+            smooth_lighting: true,
+            // Per-corner ambient occlusion stops most greedy merges on generated terrain, so
+            // greedy meshing is slower there for few saved quads (see `bench_meshers`).
+            mesher: MesherKind::Naive,
+        }
+    }
+}
+
+// Turns a chunk and its neighbours into CPU mesh data. Implementations must produce the
+// same visible surface, so they can be swapped and benchmarked against each other.
+pub trait ChunkMesher {
+    fn build(&self, neighbourhood: &ChunkNeighbourhood, settings: MeshSettings) -> ChunkMesh;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MesherKind {
+    Naive,
+    Greedy,
+}
+
+impl MesherKind {
+    pub fn mesher(self) -> &'static dyn ChunkMesher {
+        match self {
+            MesherKind::Naive => &NaiveMesher,
+            MesherKind::Greedy => &GreedyMesher,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MesherKind::Naive => "naive",
+            MesherKind::Greedy => "greedy",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            MesherKind::Naive => MesherKind::Greedy,
+            MesherKind::Greedy => MesherKind::Naive,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct LightSample {
+    occludes: bool,
+    sky_light: u8,
+    block_light: u8,
+}
+
+// Lighting of a face's four corners, in the order of the face's vertex template.
+#[derive(Clone, Copy, PartialEq)]
+struct FaceShading {
+    sky_light: [f32; 4],
+    block_light: [f32; 4],
+    // 0 (corner boxed in by two sides) to 3 (nothing around it).
+    ao: [f32; 4],
+}
+
+impl FaceShading {
+    fn flat(sky_light: u8, block_light: u8) -> Self {
+        FaceShading {
+            sky_light: [sky_light as f32; 4],
+            block_light: [block_light as f32; 4],
+            ao: [3.0; 4],
+        }
+    }
+
+    // Smooth lighting for the face of the block at `block_pos` facing `normal`. Each corner
+    // looks at the four cells in front of the face that touch it: the one straight ahead,
+    // the two beside it and the diagonal one.
+    fn smooth(
+        neighbourhood: &ChunkNeighbourhood,
+        block_pos: IVec3,
+        normal: IVec3,
+        face: CubeFace,
+    ) -> Self {
+        let front = block_pos + normal;
+        let front_sample = neighbourhood.light_sample(front);
+        let (_, [axis_a, axis_b]) = face_axes(normal);
+        let mut shading = FaceShading::flat(0, 0);
+        for (corner, vertex) in face.get_vertices_template().iter().enumerate() {
+            // Template positions are ±0.5 around the block centre, so their signs point
+            // towards the corner.
+            let mut step_a = IVec3::ZERO;
+            step_a[axis_a] = vertex.position[axis_a].signum() as i32;
+            let mut step_b = IVec3::ZERO;
+            step_b[axis_b] = vertex.position[axis_b].signum() as i32;
+
+            let side_a = neighbourhood.light_sample(front + step_a);
+            let side_b = neighbourhood.light_sample(front + step_b);
+            let diagonal = neighbourhood.light_sample(front + step_a + step_b);
+            let occludes = |sample: Option<LightSample>| sample.is_some_and(|s| s.occludes);
+
+            let sides_closed = occludes(side_a) && occludes(side_b);
+            shading.ao[corner] = if sides_closed {
+                0.0
+            } else {
+                3.0 - [side_a, side_b, diagonal]
+                    .into_iter()
+                    .filter(|&sample| occludes(sample))
+                    .count() as f32
+            };
+
+            // Light comes only from open cells; the diagonal one is cut off when both sides
+            // are solid.
+            let diagonal = if sides_closed { None } else { diagonal };
+            let (mut count, mut sky_light, mut block_light) = (0.0, 0.0, 0.0);
+            for sample in [front_sample, side_a, side_b, diagonal]
+                .into_iter()
+                .flatten()
+                .filter(|sample| !sample.occludes)
+            {
+                count += 1.0;
+                sky_light += sample.sky_light as f32;
+                block_light += sample.block_light as f32;
+            }
+            if count > 0.0 {
+                shading.sky_light[corner] = sky_light / count;
+                shading.block_light[corner] = block_light / count;
+            }
+        }
+        shading
+    }
+
+    // Only evenly lit faces can be merged into larger quads; anything else would lose the
+    // light gradient across them.
+    fn is_uniform(&self) -> bool {
+        [self.sky_light, self.block_light, self.ao]
+            .iter()
+            .all(|corners| corners.iter().all(|&value| value == corners[0]))
+    }
+
+    // Emissive blocks are lit by their own light.
+    fn with_emission(mut self, emission: u8) -> Self {
+        for level in &mut self.block_light {
+            *level = level.max(emission as f32);
+        }
+        self
+    }
+
+    // Splitting the quad along the brighter diagonal keeps a single dark corner from
+    // smearing across the whole face.
+    fn local_indices(&self, face: CubeFace) -> &'static [u16] {
+        if self.ao[0] + self.ao[2] < self.ao[1] + self.ao[3] {
+            face.get_flipped_local_indices()
+        } else {
+            face.get_local_indices()
+        }
+    }
+}
+
+// Indices are 32-bit so dense chunks (a checkerboard has over a million vertices) cannot
+// wrap around; `chunk_render` narrows them to 16-bit when the mesh is small enough.
+#[derive(Default)]
+pub struct ChunkMesh {
+    pub opaque_vertices: Vec<Vertex>,
+    pub opaque_indices: Vec<u32>,
+    pub transparent_vertices: Vec<Vertex>,
+    pub transparent_indices: Vec<u32>,
+    // Fluid surfaces, drawn translucent after everything else.
+    pub fluid_vertices: Vec<Vertex>,
+    pub fluid_indices: Vec<u32>,
+    // Range of `opaque_indices` belonging to each section, bottom to top, so sections hidden
+    // by cave culling can be skipped at draw time.
+    pub opaque_section_ranges: Vec<Range<u32>>,
+    // Which faces of each section can see each other; see `visibility`.
+    pub section_visibility: Vec<SectionVisibility>,
+}
+
+pub fn build_chunk_mesh(neighbourhood: &ChunkNeighbourhood, settings: MeshSettings) -> ChunkMesh {
+    let mut mesh = settings.mesher.mesher().build(neighbourhood, settings);
+    // Fluids are meshed the same way whichever mesher is chosen.
+    let fluid = build_fluid_faces(neighbourhood, settings);
+    mesh.fluid_vertices = fluid.vertices;
+    mesh.fluid_indices = fluid.indices;
+    mesh.section_visibility = chunk_visibility(&neighbourhood.center);
+    mesh
+}
+
+// One quad per visible face.
+pub struct NaiveMesher;
+
+impl ChunkMesher for NaiveMesher {
+    fn build(&self, neighbourhood: &ChunkNeighbourhood, settings: MeshSettings) -> ChunkMesh {
+        let chunk = &*neighbourhood.center;
+        let mut opaque = MeshBuffers::default();
+        let mut section_ranges = Vec::with_capacity(SECTION_COUNT);
+        for section in 0..SECTION_COUNT {
+            let start = opaque.indices.len() as u32;
+            if !chunk.is_section_empty(section) {
+                for (local, block) in non_air_blocks_in_section(chunk, section)
+                    .filter(|(_, block)| !block.is_transparent())
+                {
+                    let pos = world_block_pos(chunk, local);
+                    for (face, normal) in FACES {
+                        if let Some(block_face) =
+                            visible_face(neighbourhood, settings, local, &block, face, normal)
+                        {
+                            opaque.push_quad(face, pos.as_vec3(), Vec3::ONE, &block_face);
+                        }
+                    }
+                }
+            }
+            section_ranges.push(start..opaque.indices.len() as u32);
+        }
+        let transparent = build_transparent_faces(neighbourhood, settings);
+        ChunkMesh {
+            opaque_vertices: opaque.vertices,
+            opaque_indices: opaque.indices,
+            transparent_vertices: transparent.vertices,
+            transparent_indices: transparent.indices,
+            opaque_section_ranges: section_ranges,
+            ..ChunkMesh::default()
+        }
+    }
+}
+
+// Merges coplanar opaque faces with the same texture, tint and light into larger quads whose
+// texture repeats once per block. Works slice by slice through each section, so quads never
+// span sections. Transparent faces are left as single quads.
+pub struct GreedyMesher;
+
+impl ChunkMesher for GreedyMesher {
+    fn build(&self, neighbourhood: &ChunkNeighbourhood, settings: MeshSettings) -> ChunkMesh {
+        let chunk = &*neighbourhood.center;
+        let mut opaque = MeshBuffers::default();
+        let mut mask: Vec<Option<BlockFace>> = vec![None; SECTION_SIZE * SECTION_SIZE];
+        let mut section_ranges = Vec::with_capacity(SECTION_COUNT);
+
+        for section in 0..SECTION_COUNT {
+            let start = opaque.indices.len() as u32;
+            if chunk.is_section_empty(section) {
+                section_ranges.push(start..start);
+                continue;
+            }
+            let section_origin = IVec3::new(0, (section * SECTION_SIZE) as i32, 0);
+            // Each block is looked at once per face direction; read the section only once.
+            let mut opaque_blocks = [None; SECTION_SIZE * SECTION_SIZE * SECTION_SIZE];
+            for (local, block) in non_air_blocks_in_section(chunk, section) {
+                if !block.is_transparent() {
+                    opaque_blocks[section_block_index(local)] = Some(block);
+                }
+            }
+            for (face, normal) in FACES {
+                let (axis_n, [axis_a, axis_b]) = face_axes(normal);
+                for depth in 0..SECTION_SIZE as i32 {
+                    let local_at = |a: usize, b: usize| {
+                        let mut local = section_origin;
+                        local[axis_n] += depth;
+                        local[axis_a] += a as i32;
+                        local[axis_b] += b as i32;
+                        (local.x as usize, local.y as usize, local.z as usize)
+                    };
+
+                    for b in 0..SECTION_SIZE {
+                        for a in 0..SECTION_SIZE {
+                            let local = local_at(a, b);
+                            mask[b * SECTION_SIZE + a] = opaque_blocks[section_block_index(local)]
+                                .and_then(|block| {
+                                    visible_face(
+                                        neighbourhood,
+                                        settings,
+                                        local,
+                                        &block,
+                                        face,
+                                        normal,
+                                    )
+                                });
+                        }
+                    }
+
+                    for b in 0..SECTION_SIZE {
+                        let mut a = 0;
+                        while a < SECTION_SIZE {
+                            let Some(block_face) = mask[b * SECTION_SIZE + a] else {
+                                a += 1;
+                                continue;
+                            };
+                            let (mut width, mut height) = (1, 1);
+                            if block_face.shading.is_uniform() {
+                                while a + width < SECTION_SIZE
+                                    && mask[b * SECTION_SIZE + a + width] == Some(block_face)
+                                {
+                                    width += 1;
+                                }
+                                while b + height < SECTION_SIZE
+                                    && mask[(b + height) * SECTION_SIZE + a..][..width]
+                                        .iter()
+                                        .all(|cell| *cell == Some(block_face))
+                                {
+                                    height += 1;
+                                }
+                            }
+                            for row in b..b + height {
+                                mask[row * SECTION_SIZE + a..][..width].fill(None);
+                            }
+
+                            let mut size = Vec3::ONE;
+                            size[axis_a] = width as f32;
+                            size[axis_b] = height as f32;
+                            opaque.push_quad(
+                                face,
+                                world_block_pos(chunk, local_at(a, b)).as_vec3(),
+                                size,
+                                &block_face,
+                            );
+                            a += width;
+                        }
+                    }
+                }
+            }
+            section_ranges.push(start..opaque.indices.len() as u32);
+        }
+
+        let transparent = build_transparent_faces(neighbourhood, settings);
+        ChunkMesh {
+            opaque_vertices: opaque.vertices,
+            opaque_indices: opaque.indices,
+            transparent_vertices: transparent.vertices,
+            transparent_indices: transparent.indices,
+            opaque_section_ranges: section_ranges,
+            ..ChunkMesh::default()
+        }
+    }
+}
+
+// Transparent quads of a chunk with their centres, kept on the CPU so their draw order can
+// follow the camera: blending is only correct when faces further away are drawn first.
+#[derive(Default)]
+pub struct TransparentQuads {
+    quads: Vec<(Vec3, [u32; 6])>,
+}
+
+impl TransparentQuads {
+    pub fn new(vertices: &[Vertex], indices: &[u32]) -> Self {
+        let quads = indices
+            .chunks_exact(6)
+            .map(|quad| {
+                let (min, max) = quad.iter().fold(
+                    (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+                    |(min, max), &index| {
+                        let position = Vec3::from(vertices[index as usize].position);
+                        (min.min(position), max.max(position))
+                    },
+                );
+                ((min + max) / 2.0, quad.try_into().unwrap())
+            })
+            .collect();
+        TransparentQuads { quads }
+    }
+
+    // The index buffer with the quads ordered back to front as seen from `camera`. The quads
+    // keep their last order, so sorting again after a small camera move is cheap.
+    pub fn sorted_indices(&mut self, camera: Vec3) -> Vec<u32> {
+        sort_back_to_front(&mut self.quads, camera, |(centre, _)| *centre);
+        self.quads.iter().flat_map(|(_, quad)| *quad).collect()
+    }
+}
+
+// Orders `items` so the one whose centre is furthest from `camera` comes first.
+pub fn sort_back_to_front<T>(items: &mut [T], camera: Vec3, centre: impl Fn(&T) -> Vec3) {
+    items.sort_by(|a, b| {
+        camera
+            .distance_squared(centre(b))
+            .total_cmp(&camera.distance_squared(centre(a)))
+    });
+}
+
+// Leaves, glass and other transparent blocks, one quad per visible face for both meshers.
+fn build_transparent_faces(
+    neighbourhood: &ChunkNeighbourhood,
+    settings: MeshSettings,
+) -> MeshBuffers {
+    let chunk = &*neighbourhood.center;
+    let mut transparent = MeshBuffers::default();
+    for (local, block) in
+        non_air_blocks(chunk).filter(|(_, block)| block.is_transparent() && !block.is_fluid())
+    {
+        let pos = world_block_pos(chunk, local);
+        for (face, normal) in FACES {
+            if let Some(block_face) =
+                visible_face(neighbourhood, settings, local, &block, face, normal)
+            {
+                transparent.push_quad(face, pos.as_vec3(), Vec3::ONE, &block_face);
+            }
+        }
+    }
+    transparent
+}
+
+// Water and other fluids, one quad per visible face like other transparent blocks. The top
+// edge of each face is lowered to the fluid's surface, which at every corner is the average
+// height of the fluid around it, so neighbouring levels join up into a sloped surface.
+fn build_fluid_faces(neighbourhood: &ChunkNeighbourhood, settings: MeshSettings) -> MeshBuffers {
+    let chunk = &*neighbourhood.center;
+    let mut fluid = MeshBuffers::default();
+    for (local, block) in non_air_blocks(chunk).filter(|(_, block)| block.is_fluid()) {
+        let pos = world_block_pos(chunk, local);
+        for (face, normal) in FACES {
+            let Some(block_face) =
+                visible_face(neighbourhood, settings, local, &block, face, normal)
+            else {
+                continue;
+            };
+            let first_vertex = fluid.vertices.len();
+            fluid.push_quad(face, pos.as_vec3(), Vec3::ONE, &block_face);
+            let top_y = (pos.y + 1) as f32;
+            for vertex in &mut fluid.vertices[first_vertex..] {
+                if vertex.position[1] == top_y {
+                    let corner =
+                        IVec3::new(vertex.position[0] as i32, pos.y, vertex.position[2] as i32);
+                    vertex.position[1] =
+                        pos.y as f32 + fluid_corner_height(neighbourhood, block.block_type, corner);
+                }
+            }
+        }
+    }
+    fluid
+}
+
+// Surface height at the block corner `corner` (the corner's x and z, the fluid's y),
+// averaged over the fluid columns that share it. Fluid with more of itself above a column
+// reaches the top of the block.
+fn fluid_corner_height(
+    neighbourhood: &ChunkNeighbourhood,
+    fluid_type: BlockType,
+    corner: IVec3,
+) -> f32 {
+    let block_at = |pos: IVec3| {
+        neighbourhood
+            .get_block_at_world(pos.x as f32, pos.y as f32, pos.z as f32)
+            .filter(|block| block.block_type == fluid_type)
+    };
+    let mut total_height = 0.0;
+    let mut columns = 0;
+    for (dx, dz) in [(-1, -1), (-1, 0), (0, -1), (0, 0)] {
+        let column = corner + IVec3::new(dx, 0, dz);
+        if block_at(column + IVec3::Y).is_some() {
+            return 1.0;
+        }
+        if let Some(fluid) = block_at(column) {
+            total_height += fluid.fluid_height();
+            columns += 1;
+        }
+    }
+    // The fluid block itself is always one of the columns.
+    total_height / columns.max(1) as f32
+}
+
+// Every non-air block of a chunk with its local position. Empty sections are all air and
+// produce no faces of their own.
+fn non_air_blocks(chunk: &Chunk) -> impl Iterator<Item = ((usize, usize, usize), Block)> + '_ {
+    (0..SECTION_COUNT)
+        .filter(|&index| !chunk.is_section_empty(index))
+        .flat_map(|section| non_air_blocks_in_section(chunk, section))
+}
+
+fn non_air_blocks_in_section(
+    chunk: &Chunk,
+    section: usize,
+) -> impl Iterator<Item = ((usize, usize, usize), Block)> + '_ {
+    (0..CHUNK_WIDTH)
+        .flat_map(move |x| {
+            (section * SECTION_SIZE..(section + 1) * SECTION_SIZE)
+                .flat_map(move |y| (0..CHUNK_DEPTH).map(move |z| (x, y, z)))
+        })
+        .filter_map(|(x, y, z)| {
+            chunk
+                .get_block(x, y, z)
+                .filter(|block| block.block_type != BlockType::AIR)
+                .map(|block| ((x, y, z), block))
+        })
+}
+
+// Index of a block within its section, for per-section scratch arrays.
+fn section_block_index((x, y, z): (usize, usize, usize)) -> usize {
+    ((y % SECTION_SIZE) * SECTION_SIZE + z) * SECTION_SIZE + x
+}
+
+fn world_block_pos(chunk: &Chunk, (x, y, z): (usize, usize, usize)) -> IVec3 {
+    IVec3::new(
+        chunk.coord.0 * CHUNK_WIDTH as i32 + x as i32,
+        local_y_to_world(y),
+        chunk.coord.1 * CHUNK_DEPTH as i32 + z as i32,
+    )
+}
+
+// The axis a face's normal points along and the two axes spanning the face (x = 0, y = 1,
+// z = 2).
+fn face_axes(normal: IVec3) -> (usize, [usize; 2]) {
+    match normal {
+        IVec3 { x: 0, y: 0, .. } => (2, [0, 1]),
+        IVec3 { x: 0, .. } => (1, [0, 2]),
+        _ => (0, [1, 2]),
+    }
+}
+
+// A visible block face with everything that decides whether it can merge with its
+// neighbours.
+#[derive(Clone, Copy, PartialEq)]
+struct BlockFace {
+    // Top-left corner of the face's texture in the atlas.
+    atlas_tile: [f32; 2],
+    tint: [f32; 3],
+    tree_id: u32,
+    shading: FaceShading,
+}
+
+// The face of the block at `local` pointing along `normal`, if it can be seen. Opaque blocks
+// show faces towards any transparent block; transparent blocks only towards transparent
+// blocks of another type, so leaves next to leaves draw no inner faces.
+fn visible_face(
+    neighbourhood: &ChunkNeighbourhood,
+    settings: MeshSettings,
+    local: (usize, usize, usize),
+    block: &Block,
+    face: CubeFace,
+    normal: IVec3,
+) -> Option<BlockFace> {
+    let chunk = &*neighbourhood.center;
+    let pos = world_block_pos(chunk, local);
+    let neighbour_pos = pos + normal;
+
+    let (face_sky_light, face_block_light) = if (MIN_Y..MAX_Y).contains(&neighbour_pos.y) {
+        let neighbour = neighbourhood.get_block_at_world(
+            neighbour_pos.x as f32,
+            neighbour_pos.y as f32,
+            neighbour_pos.z as f32,
+        )?;
+        let hides_face = !neighbour.is_transparent()
+            || (block.is_transparent() && neighbour.block_type == block.block_type);
+        if hides_face {
+            return None;
+        }
+        (neighbour.sky_light, neighbour.block_light)
+    } else {
+        (0, 0)
+    };
+
+    let shading = if settings.smooth_lighting {
+        FaceShading::smooth(neighbourhood, pos, normal, face)
+    } else {
+        FaceShading::flat(face_sky_light, face_block_light)
+    }
+    .with_emission(block.light_emission());
+
+    let face_index = face.index();
+    let tile = block.get_texture_atlas_indices()[face_index];
+    Some(BlockFace {
+        atlas_tile: [tile[0] / ATLAS_COLS, tile[1] / ATLAS_ROWS],
+        tint: chunk
+            .get_biome(local.0, local.2)
+            .tint_color(block.get_face_tints()[face_index]),
+        tree_id: if block.is_transparent() {
+            block.tree_id.unwrap_or(0)
+        } else {
+            0
+        },
+        shading,
+    })
+}
+
+// Texture corner of each vertex of a face's template as (u, v), v growing downwards.
+fn unit_uvs(face: CubeFace) -> [[f32; 2]; 4] {
+    match face {
+        // Bottom left, top left, top right, bottom right.
+        CubeFace::Front | CubeFace::Right | CubeFace::Left | CubeFace::Bottom => {
+            [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]
+        }
+        // Bottom left, bottom right, top right, top left.
+        CubeFace::Back | CubeFace::Top => [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+    }
+}
+
+// The axes a face's texture u and v run along.
+fn uv_axes(face: CubeFace) -> (usize, usize) {
+    match face {
+        CubeFace::Front | CubeFace::Back => (0, 1),
+        CubeFace::Right | CubeFace::Left => (2, 1),
+        CubeFace::Top | CubeFace::Bottom => (0, 2),
+    }
+}
+
+#[derive(Default)]
+struct MeshBuffers {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuffers {
+    // Appends `block_face` as a quad covering `size` blocks from the block corner
+    // `min_corner`. The size along the face's normal must be 1. UVs count in blocks, so the
+    // texture repeats across merged quads.
+    fn push_quad(&mut self, face: CubeFace, min_corner: Vec3, size: Vec3, block_face: &BlockFace) {
+        let base_index = self.vertices.len() as u32;
+        let (u_axis, v_axis) = uv_axes(face);
+        let unit_uvs = unit_uvs(face);
+        for (i, template) in face.get_vertices_template().iter().enumerate() {
+            self.vertices.push(Vertex {
+                position: (min_corner + (Vec3::from(template.position) + 0.5) * size).into(),
+                tint: block_face.tint,
+                uv: [unit_uvs[i][0] * size[u_axis], unit_uvs[i][1] * size[v_axis]],
+                tree_id: block_face.tree_id,
+                sky_light: block_face.shading.sky_light[i],
+                block_light: block_face.shading.block_light[i],
+                ao: block_face.shading.ao[i],
+                atlas_tile: block_face.atlas_tile,
+            });
+        }
+        self.indices.extend(
+            block_face
+                .shading
+                .local_indices(face)
+                .iter()
+                .map(|&index| base_index + index as u32),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::generate_chunk_data;
+    use std::time::Instant;
+
+    fn neighbourhood_of(chunk: Chunk) -> ChunkNeighbourhood {
+        ChunkNeighbourhood {
+            center: Arc::new(chunk),
+            neighbours: Default::default(),
+        }
+    }
+
+    // The top face of the stone block at local (8, 64, 8), found by its four vertices all
+    // lying in the plane above the block.
+    fn top_face_of_block(mesh: &ChunkMesh) -> [Vertex; 4] {
+        let top_y = local_y_to_world(64) as f32 + 1.0;
+        mesh.opaque_vertices
+            .chunks_exact(4)
+            .find(|face| {
+                face.iter().all(|vertex| {
+                    vertex.position[1] == top_y && (8.0..=9.0).contains(&vertex.position[0])
+                })
+            })
+            .expect("top face is meshed")
+            .try_into()
+            .unwrap()
+    }
+
+    // A block with a wall block diagonally above it on the +X side.
+    fn block_beside_wall() -> ChunkNeighbourhood {
+        let mut chunk = Chunk::new(0, 0);
+        chunk.set_block(8, 64, 8, BlockType::STONE).unwrap();
+        chunk.set_block(9, 65, 8, BlockType::STONE).unwrap();
+        chunk.calculate_sky_light();
+        neighbourhood_of(chunk)
+    }
+
+    #[test]
+    fn test_ambient_occlusion_darkens_corners_next_to_a_wall() {
+        let mesh = build_chunk_mesh(&block_beside_wall(), MeshSettings::default());
+        for vertex in top_face_of_block(&mesh) {
+            let expected_ao = if vertex.position[0] == 9.0 { 2.0 } else { 3.0 };
+            assert_eq!(vertex.ao, expected_ao, "corner at {:?}", vertex.position);
+            // The wall itself is opaque and does not pull the light down.
+            assert_eq!(vertex.sky_light, 15.0);
+        }
+    }
+
+    #[test]
+    fn test_flat_lighting_has_no_occlusion() {
+        let settings = MeshSettings {
+            smooth_lighting: false,
+            ..MeshSettings::default()
+        };
+        let mesh = build_chunk_mesh(&block_beside_wall(), settings);
+        for vertex in top_face_of_block(&mesh) {
+            assert_eq!(vertex.ao, 3.0);
+            assert_eq!(vertex.sky_light, 15.0);
+        }
+    }
+
+    fn opaque_face_count(mesh: &ChunkMesh) -> usize {
+        mesh.opaque_vertices.len() / 4
+    }
+
+    fn transparent_face_count(mesh: &ChunkMesh) -> usize {
+        mesh.transparent_vertices.len() / 4
+    }
+
+    fn chunk_with(blocks: &[((usize, usize, usize), BlockType)]) -> ChunkNeighbourhood {
+        let mut chunk = Chunk::new(0, 0);
+        for &((x, y, z), block_type) in blocks {
+            chunk.set_block(x, y, z, block_type).unwrap();
+        }
+        chunk.calculate_sky_light();
+        neighbourhood_of(chunk)
+    }
+
+    #[test]
+    fn test_single_block_has_six_faces() {
+        let neighbourhood = chunk_with(&[((8, 64, 8), BlockType::STONE)]);
+        for kind in [MesherKind::Naive, MesherKind::Greedy] {
+            let mesh = build_chunk_mesh(&neighbourhood, settings_with(kind));
+            assert_eq!(opaque_face_count(&mesh), 6, "{} mesher", kind.name());
+            assert_eq!(mesh.opaque_indices.len(), 6 * 6);
+            assert_eq!(transparent_face_count(&mesh), 0);
+        }
+    }
+
+    #[test]
+    fn test_section_ranges_cover_each_sections_faces() {
+        let neighbourhood = chunk_with(&[
+            ((8, 20, 8), BlockType::STONE),
+            ((8, 64, 8), BlockType::STONE),
+        ]);
+        for kind in [MesherKind::Naive, MesherKind::Greedy] {
+            let mesh = build_chunk_mesh(&neighbourhood, settings_with(kind));
+            assert_eq!(mesh.opaque_section_ranges.len(), SECTION_COUNT);
+            assert_eq!(mesh.section_visibility.len(), SECTION_COUNT);
+            let non_empty: Vec<_> = mesh
+                .opaque_section_ranges
+                .iter()
+                .enumerate()
+                .filter(|(_, range)| !range.is_empty())
+                .map(|(section, range)| (section, range.clone()))
+                .collect();
+            assert_eq!(
+                non_empty,
+                vec![(1, 0..36), (4, 36..72)],
+                "{} mesher",
+                kind.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_adjacent_blocks_hide_their_shared_faces() {
+        let neighbourhood = chunk_with(&[
+            ((8, 64, 8), BlockType::STONE),
+            ((9, 64, 8), BlockType::STONE),
+        ]);
+        let naive = build_chunk_mesh(&neighbourhood, settings_with(MesherKind::Naive));
+        assert_eq!(opaque_face_count(&naive), 10);
+        // The greedy mesher covers the same surface with the long sides merged. Flat lighting
+        // keeps the shaded bottom faces from differing in light.
+        let settings = MeshSettings {
+            smooth_lighting: false,
+            mesher: MesherKind::Greedy,
+        };
+        let greedy = build_chunk_mesh(&neighbourhood, settings);
+        assert_eq!(opaque_face_count(&greedy), 6);
+        assert_eq!(
+            quad_areas(&greedy).iter().sum::<f32>(),
+            quad_areas(&naive).iter().sum::<f32>()
+        );
+    }
+
+    #[test]
+    fn test_transparent_quads_sort_back_to_front() {
+        let neighbourhood = chunk_with(&[
+            ((2, 64, 8), BlockType::OAK_LEAVES),
+            ((12, 64, 8), BlockType::OAK_LEAVES),
+        ]);
+        let mesh = build_chunk_mesh(&neighbourhood, MeshSettings::default());
+        let mut quads =
+            TransparentQuads::new(&mesh.transparent_vertices, &mesh.transparent_indices);
+        let first_quad_x =
+            |indices: &[u32]| mesh.transparent_vertices[indices[0] as usize].position[0];
+
+        let from_west = quads.sorted_indices(Vec3::new(-10.0, local_y_to_world(64) as f32, 8.5));
+        assert_eq!(from_west.len(), mesh.transparent_indices.len());
+        assert!(
+            first_quad_x(&from_west) >= 12.0,
+            "the far leaf is drawn first"
+        );
+
+        let from_east = quads.sorted_indices(Vec3::new(30.0, local_y_to_world(64) as f32, 8.5));
+        assert!(
+            first_quad_x(&from_east) <= 3.0,
+            "the order follows the camera"
+        );
+    }
+
+    #[test]
+    fn test_transparent_blocks_hide_faces_only_towards_their_own_type() {
+        let neighbourhood = chunk_with(&[
+            ((8, 64, 8), BlockType::OAK_LEAVES),
+            ((9, 64, 8), BlockType::OAK_LEAVES),
+            ((8, 64, 9), BlockType::STONE),
+        ]);
+        let mesh = build_chunk_mesh(&neighbourhood, MeshSettings::default());
+        // Two leaves with their shared faces culled, including the one touching stone.
+        assert_eq!(transparent_face_count(&mesh), 10 - 1);
+        // The stone still shows its face towards the leaves.
+        assert_eq!(opaque_face_count(&mesh), 6);
+    }
+
+    #[test]
+    fn test_fluid_goes_into_its_own_mesh_with_a_lowered_surface() {
+        let neighbourhood = chunk_with(&[((8, 64, 8), BlockType::WATER)]);
+        let mesh = build_chunk_mesh(&neighbourhood, MeshSettings::default());
+        assert_eq!(transparent_face_count(&mesh), 0);
+        assert_eq!(mesh.fluid_vertices.len(), 6 * 4);
+        assert_eq!(mesh.fluid_indices.len(), 6 * 6);
+        let surface_y = local_y_to_world(64) as f32 + 8.0 / 9.0;
+        let top_vertices = mesh
+            .fluid_vertices
+            .iter()
+            .filter(|vertex| vertex.position[1] > local_y_to_world(64) as f32)
+            .count();
+        assert_eq!(top_vertices, 3 * 4);
+        assert!(
+            mesh.fluid_vertices
+                .iter()
+                .all(|vertex| vertex.position[1] <= surface_y)
+        );
+    }
+
+    #[test]
+    fn test_faces_towards_missing_neighbours_are_hidden() {
+        let neighbourhood = chunk_with(&[((0, 64, 8), BlockType::STONE)]);
+        let mesh = build_chunk_mesh(&neighbourhood, settings_with(MesherKind::Naive));
+        assert_eq!(opaque_face_count(&mesh), 5);
+        assert!(
+            mesh.opaque_vertices
+                .iter()
+                .all(|vertex| vertex.position[0] >= 0.0)
+        );
+    }
+
+    #[test]
+    fn test_checkerboard_chunk_indices_do_not_wrap() {
+        // Three sections of checkerboard: every block shows all six faces, which is more
+        // vertices than 16-bit indices can address.
+        let mut blocks = Vec::new();
+        for x in 1..CHUNK_WIDTH - 1 {
+            for y in 64..64 + 3 * SECTION_SIZE {
+                for z in 1..CHUNK_DEPTH - 1 {
+                    if (x + y + z) % 2 == 0 {
+                        blocks.push(((x, y, z), BlockType::STONE));
+                    }
+                }
+            }
+        }
+        let neighbourhood = chunk_with(&blocks);
+        for kind in [MesherKind::Naive, MesherKind::Greedy] {
+            let mesh = build_chunk_mesh(&neighbourhood, settings_with(kind));
+            assert_eq!(opaque_face_count(&mesh), blocks.len() * 6);
+            assert!(mesh.opaque_vertices.len() > u16::MAX as usize);
+            // Each quad's indices point at its own four vertices.
+            for (quad, indices) in mesh.opaque_indices.chunks_exact(6).enumerate() {
+                let first_vertex = quad as u32 * 4;
+                assert!(
+                    indices
+                        .iter()
+                        .all(|&index| (first_vertex..first_vertex + 4).contains(&index)),
+                    "{} mesher, quad {}",
+                    kind.name(),
+                    quad
+                );
+            }
+        }
+    }
+
+    fn settings_with(mesher: MesherKind) -> MeshSettings {
+        MeshSettings {
+            mesher,
+            ..MeshSettings::default()
+        }
+    }
+
+    // A full layer of blocks at local y 64, split at x = `split_x` between two block types.
+    fn layer(west: BlockType, east: BlockType, split_x: usize) -> ChunkNeighbourhood {
+        let mut chunk = Chunk::new(0, 0);
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                let block_type = if x < split_x { west } else { east };
+                chunk.set_block(x, 64, z, block_type).unwrap();
+            }
+        }
+        chunk.calculate_sky_light();
+        neighbourhood_of(chunk)
+    }
+
+    // Area of each opaque quad, in blocks.
+    fn quad_areas(mesh: &ChunkMesh) -> Vec<f32> {
+        mesh.opaque_vertices
+            .chunks_exact(4)
+            .map(|quad| {
+                let corner = Vec3::from(quad[0].position);
+                let side_a = Vec3::from(quad[1].position) - corner;
+                let side_b = Vec3::from(quad[3].position) - corner;
+                side_a.cross(side_b).length()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_greedy_mesher_merges_a_flat_layer() {
+        let neighbourhood = layer(BlockType::STONE, BlockType::STONE, 0);
+        let naive = build_chunk_mesh(&neighbourhood, settings_with(MesherKind::Naive));
+        let greedy = build_chunk_mesh(&neighbourhood, settings_with(MesherKind::Greedy));
+
+        // Top and bottom; the sides face missing neighbours and stay hidden.
+        assert_eq!(quad_areas(&naive).len(), 2 * 256);
+        assert_eq!(quad_areas(&greedy), vec![256.0, 256.0]);
+        // The texture repeats once per block across the merged quad.
+        for vertex in &greedy.opaque_vertices {
+            assert!(vertex.uv.iter().all(|&uv| uv == 0.0 || uv == 16.0));
+        }
+    }
+
+    #[test]
+    fn test_greedy_mesher_keeps_different_textures_apart() {
+        let neighbourhood = layer(BlockType::STONE, BlockType::DIRT, 8);
+        let greedy = build_chunk_mesh(&neighbourhood, settings_with(MesherKind::Greedy));
+        assert_eq!(quad_areas(&greedy), vec![128.0; 4]);
+        for quad in greedy.opaque_vertices.chunks_exact(4) {
+            assert!(
+                quad.iter()
+                    .all(|vertex| vertex.atlas_tile == quad[0].atlas_tile)
+            );
+        }
+    }
+
+    // Compares the meshers on real terrain:
+    // `cargo test --release bench_meshers -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_meshers_on_generated_terrain() {
+        let mut world = World::new(12345);
+        for cx in -1..=1 {
+            for cz in -1..=1 {
+                let (chunk, placements) =
+                    generate_chunk_data(&*world.generator(), world.seed(), (cx, cz));
+                world.insert_chunk(chunk, placements);
+            }
+        }
+        let neighbourhood = ChunkNeighbourhood::from_world(&world, 0, 0).unwrap();
+        const RUNS: u32 = 20;
+        for kind in [MesherKind::Naive, MesherKind::Greedy] {
+            let settings = settings_with(kind);
+            let start = Instant::now();
+            let mut mesh = ChunkMesh::default();
+            for _ in 0..RUNS {
+                mesh = build_chunk_mesh(&neighbourhood, settings);
+            }
+            println!(
+                "{}: {:?} per chunk, {} opaque quads",
+                kind.name(),
+                start.elapsed() / RUNS,
+                mesh.opaque_vertices.len() / 4
+            );
+        }
+    }
+}
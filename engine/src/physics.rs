@@ -5,11 +5,27 @@ pub const GRAVITY: f32 = 9.81 * 2.8; // m/s^2, doubled for more "gamey" feel
 pub const JUMP_FORCE: f32 = 8.0; // m/s
 pub const WALK_SPEED: f32 = 4.0; // m/s
 pub const FRICTION_COEFFICIENT: f32 = 0.8; // Dimensionless, used to scale velocity down
+pub const SPRINT_SPEED_MULTIPLIER: f32 = 1.3; // Applied to WALK_SPEED while sprinting
+pub const SNEAK_SPEED_MULTIPLIER: f32 = 0.3; // Applied to WALK_SPEED while sneaking
+pub const FLY_SPEED: f32 = 10.0; // m/s, horizontal speed while flying or spectating
+pub const FLY_VERTICAL_SPEED: f32 = 6.0; // m/s, while jump or sneak is held in flight
+pub const STEP_HEIGHT: f32 = 1.0; // meters, so a walking player steps onto single-block ledges without jumping
+pub const STEP_CAMERA_EASE_RATE: f32 = 15.0; // 1/s, how quickly the camera catches up after a step
+pub const SNEAK_EDGE_PROBE_DEPTH: f32 = 0.6; // meters, how far below the feet a sneaking player looks for ground
+
+// Fluid Constants
+pub const FLUID_GRAVITY: f32 = GRAVITY * 0.25; // m/s^2, gravity while in fluid
+pub const FLUID_BUOYANCY: f32 = 10.0; // m/s^2 upwards when fully submerged, scaled by depth
+pub const FLUID_DRAG: f32 = 2.0; // 1/s, fraction of velocity lost per second
+pub const SWIM_SPEED: f32 = 2.5; // m/s
+pub const SWIM_UP_SPEED: f32 = 4.0; // m/s, while jump is held in fluid
 
 // Player Dimensions
 pub const PLAYER_WIDTH: f32 = 0.6; // meters
 pub const PLAYER_HEIGHT: f32 = 1.8; // meters
 pub const PLAYER_EYE_HEIGHT: f32 = 1.6; // meters, from feet
+pub const PLAYER_SNEAK_HEIGHT: f32 = 1.5; // meters
+pub const PLAYER_SNEAK_EYE_HEIGHT: f32 = 1.27; // meters, from feet
 pub const PLAYER_HALF_WIDTH: f32 = PLAYER_WIDTH / 2.0;
 
 #[derive(Debug, Clone, Copy)]
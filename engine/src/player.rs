@@ -1,7 +1,14 @@
 use glam::Vec3;
 use crate::physics::{AABB, PLAYER_HEIGHT, PLAYER_HALF_WIDTH, GRAVITY, JUMP_FORCE, WALK_SPEED, FRICTION_COEFFICIENT};
+use crate::physics::{FLUID_BUOYANCY, FLUID_DRAG, FLUID_GRAVITY, SWIM_SPEED, SWIM_UP_SPEED};
+use crate::physics::{PLAYER_EYE_HEIGHT, PLAYER_SNEAK_EYE_HEIGHT, PLAYER_SNEAK_HEIGHT};
+use crate::physics::{SNEAK_EDGE_PROBE_DEPTH, SNEAK_SPEED_MULTIPLIER, SPRINT_SPEED_MULTIPLIER};
+use crate::physics::{FLY_SPEED, FLY_VERTICAL_SPEED};
+use crate::physics::{STEP_CAMERA_EASE_RATE, STEP_HEIGHT};
+use serde::{Deserialize, Serialize};
 // Remove direct dependency on Chunk, will use World instead
 // use crate::chunk::Chunk;
+use crate::block::BlockType;
 use crate::world::World; // Import World
 
 // Input state for player movement intentions
@@ -13,14 +20,45 @@ pub struct PlayerMovementIntention {
     pub left: bool,
     pub right: bool,
     pub jump: bool,
-    // Potentially add sprint, crouch flags here later
+    pub sprint: bool, // Sprint key held; starts sprinting when moving forward
+    pub sneak: bool,
+}
+
+// How the player moves. Flying ignores gravity and moves up and down with jump and sneak;
+// spectators fly too, and also pass through blocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementMode {
+    #[default]
+    Walking,
+    Flying,
+    Spectator,
+}
+
+impl MovementMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            MovementMode::Walking => "walking",
+            MovementMode::Flying => "flying",
+            MovementMode::Spectator => "spectator",
+        }
+    }
 }
 
 pub struct Player {
     pub position: Vec3, // Position of the player's feet, centered horizontally
+    pub previous_position: Vec3, // Position before the last physics tick, for interpolation
     pub velocity: Vec3,
     pub local_bounding_box: AABB, // Relative to player's position (feet)
     pub on_ground: bool,
+    pub in_fluid: bool, // Any part of the bounding box is inside a fluid
+    pub sprinting: bool, // Lasts until the player stops moving forward or sneaks
+    pub sneaking: bool,  // Crouched: lower eyes, shorter bounding box, stays on block edges
+    pub movement_mode: MovementMode,
+    pub step_height: f32, // Ledges up to this high are stepped onto without jumping
+    // Camera drop left after stepping up (negative), eased back to 0 so the view glides up.
+    step_camera_offset: f32,
+    previous_step_camera_offset: f32,
 
     // Camera orientation fields
     pub yaw: f32,   // Radians. Rotation around the Y axis (vertical)
@@ -35,12 +73,20 @@ impl Player {
     pub fn new(initial_position: Vec3, initial_yaw: f32, initial_pitch: f32, mouse_sensitivity: f32) -> Self {
         Self {
             position: initial_position,
+            previous_position: initial_position,
             velocity: Vec3::ZERO,
             local_bounding_box: AABB {
                 min: Vec3::new(-PLAYER_HALF_WIDTH, 0.0, -PLAYER_HALF_WIDTH),
                 max: Vec3::new(PLAYER_HALF_WIDTH, PLAYER_HEIGHT, PLAYER_HALF_WIDTH),
             },
             on_ground: false,
+            in_fluid: false,
+            sprinting: false,
+            sneaking: false,
+            movement_mode: MovementMode::Walking,
+            step_height: STEP_HEIGHT,
+            step_camera_offset: 0.0,
+            previous_step_camera_offset: 0.0,
             yaw: initial_yaw,
             pitch: initial_pitch,
             mouse_sensitivity,
@@ -69,6 +115,11 @@ impl Player {
     // pub fn update_physics_and_collision(&mut self, dt: f32, chunk: &crate::chunk::Chunk) {
     // pub fn update_physics_and_collision(&mut self, dt: f32, chunk: &Chunk) { // Old signature
     pub fn update_physics_and_collision(&mut self, dt: f32, world: &World) { // New signature with World
+        self.previous_position = self.position;
+        self.previous_step_camera_offset = self.step_camera_offset;
+        self.step_camera_offset *= (-STEP_CAMERA_EASE_RATE * dt).exp();
+        self.update_stance(world);
+
         // 1. Apply Inputs & Intentions
         let mut intended_horizontal_velocity = Vec3::ZERO;
         // let forward_direction = Vec3::new(self.yaw.cos(), 0.0, self.yaw.sin()).normalize_or_zero(); // Unused
@@ -89,6 +140,10 @@ impl Player {
                                                                                                     // Front vector (horizontal part) based on yaw:
         let horizontal_forward = Vec3::new(self.yaw.cos(), 0.0, self.yaw.sin()).normalize_or_zero();
 
+        // How deep the player is in fluid, from the blocks around it before moving.
+        let submersion = self.submersion(&get_nearby_block_aabbs(&self.get_world_bounding_box(), world));
+        self.in_fluid = submersion > 0.0;
+
 
         if self.movement_intention.forward {
             intended_horizontal_velocity += horizontal_forward;
@@ -112,7 +167,21 @@ impl Player {
         }
 
         if intended_horizontal_velocity.length_squared() > 0.0 {
-            intended_horizontal_velocity = intended_horizontal_velocity.normalize() * WALK_SPEED;
+            let speed = if self.movement_mode != MovementMode::Walking {
+                FLY_SPEED
+            } else if self.in_fluid {
+                SWIM_SPEED
+            } else {
+                WALK_SPEED
+            };
+            let speed = if self.sneaking {
+                speed * SNEAK_SPEED_MULTIPLIER
+            } else if self.sprinting {
+                speed * SPRINT_SPEED_MULTIPLIER
+            } else {
+                speed
+            };
+            intended_horizontal_velocity = intended_horizontal_velocity.normalize() * speed;
             self.velocity.x = intended_horizontal_velocity.x;
             self.velocity.z = intended_horizontal_velocity.z;
         } else {
@@ -134,16 +203,43 @@ impl Player {
         }
 
         // 2. Apply Gravity
-        self.velocity.y -= GRAVITY * dt;
+        if self.movement_mode != MovementMode::Walking {
+            // No gravity in flight: jump and sneak move straight up and down.
+            let mut vertical_direction = 0.0;
+            if self.movement_intention.jump {
+                vertical_direction += 1.0;
+            }
+            if self.movement_intention.sneak {
+                vertical_direction -= 1.0;
+            }
+            self.velocity.y = vertical_direction * FLY_VERTICAL_SPEED;
+        } else if self.in_fluid {
+            // Buoyancy grows with depth, so the player settles where it balances the weaker
+            // gravity and bobs around that point while the drag damps it out.
+            self.velocity.y += (FLUID_BUOYANCY * submersion - FLUID_GRAVITY) * dt;
+            self.velocity *= (1.0 - FLUID_DRAG * dt).max(0.0);
+        } else {
+            self.velocity.y -= GRAVITY * dt;
+        }
 
         // 3. Handle Jumping
-        if self.movement_intention.jump && self.on_ground {
+        // `jump` follows the key, so holding it keeps swimming up (or jumping again on landing).
+        let walking = self.movement_mode == MovementMode::Walking;
+        if walking && self.movement_intention.jump && self.on_ground {
             self.velocity.y = JUMP_FORCE;
             self.on_ground = false; // Will be re-evaluated during Y-collision
+        } else if walking && self.movement_intention.jump && self.in_fluid {
+            // Swim up. Kept up until the feet leave the fluid, which is enough to climb out
+            // onto a bank one block above the surface.
+            self.velocity.y = self.velocity.y.max(SWIM_UP_SPEED);
         }
-        // Reset jump intention so it's not sticky
-        self.movement_intention.jump = false;
 
+        // Spectators pass through everything, so there is nothing to resolve.
+        if self.movement_mode == MovementMode::Spectator {
+            self.position += self.velocity * dt;
+            self.on_ground = false;
+            return;
+        }
 
         // 4. Collision Detection and Resolution (Axis-by-Axis)
         let mut desired_move = self.velocity * dt;
@@ -155,7 +251,7 @@ impl Player {
         // let nearby_y_blocks = get_nearby_block_aabbs(&player_world_box, chunk); // Old call
         let nearby_y_blocks = get_nearby_block_aabbs(&player_world_box, world); // New call with world
 
-        for block_box in nearby_y_blocks {
+        for block_box in nearby_y_blocks.solid {
             if player_world_box.intersects(&block_box) {
                 if desired_move.y > 0.0 { // Moving up (hit ceiling)
                     self.position.y = block_box.min.y - self.local_bounding_box.max.y - 0.0001; // Subtract small epsilon
@@ -170,15 +266,26 @@ impl Player {
                 break;
             }
         }
+        // Flying down onto the ground lands the player.
+        if self.on_ground && self.movement_mode == MovementMode::Flying {
+            self.movement_mode = MovementMode::Walking;
+        }
 
         // --- X-AXIS COLLISION ---
+        if self.sneaking && self.on_ground {
+            desired_move.x = self.clamp_to_edge(Vec3::new(desired_move.x, 0.0, 0.0), world).x;
+        }
+        let x_start = self.position;
         self.position.x += desired_move.x;
         player_world_box = self.get_world_bounding_box(); // Update box for X-movement, using Y-resolved position
         // let nearby_x_blocks = get_nearby_block_aabbs(&player_world_box, chunk); // Old call
         let nearby_x_blocks = get_nearby_block_aabbs(&player_world_box, world); // New call with world
 
-        for block_box in nearby_x_blocks {
+        for block_box in nearby_x_blocks.solid {
             if player_world_box.intersects(&block_box) {
+                if self.try_step_up(x_start, Vec3::new(desired_move.x, 0.0, 0.0), world) {
+                    break;
+                }
                 if desired_move.x > 0.0 { // Moving right
                     self.position.x = block_box.min.x - self.local_bounding_box.max.x - 0.0001;
                 } else { // Moving left
@@ -193,13 +300,20 @@ impl Player {
         }
 
         // --- Z-AXIS COLLISION ---
+        if self.sneaking && self.on_ground {
+            desired_move.z = self.clamp_to_edge(Vec3::new(0.0, 0.0, desired_move.z), world).z;
+        }
+        let z_start = self.position;
         self.position.z += desired_move.z;
         player_world_box = self.get_world_bounding_box(); // Update box for Z-movement
         // let nearby_z_blocks = get_nearby_block_aabbs(&player_world_box, chunk); // Old call
         let nearby_z_blocks = get_nearby_block_aabbs(&player_world_box, world); // New call with world
 
-        for block_box in nearby_z_blocks {
+        for block_box in nearby_z_blocks.solid {
             if player_world_box.intersects(&block_box) {
+                if self.try_step_up(z_start, Vec3::new(0.0, 0.0, desired_move.z), world) {
+                    break;
+                }
                 if desired_move.z > 0.0 { // Moving "forward" relative to world +Z (e.g. larger Z values)
                     self.position.z = block_box.min.z - self.local_bounding_box.max.z - 0.0001;
                 } else { // Moving "backward" relative to world +Z
@@ -212,18 +326,184 @@ impl Player {
         }
     }
 
+    // Retries a blocked horizontal move from `step_height` higher up. If the player has room to
+    // rise there and then make the move, it settles onto the top of what it stepped over and
+    // the camera is left behind to ease up after it. Returns whether the player stepped.
+    fn try_step_up(&mut self, start: Vec3, horizontal_move: Vec3, world: &World) -> bool {
+        if !self.on_ground || self.movement_mode != MovementMode::Walking {
+            return false;
+        }
+        let step = Vec3::new(0.0, self.step_height, 0.0);
+        let start_box = self.bounding_box_at(start);
+        let raised_box = self.bounding_box_at(start + step + horizontal_move);
+        // The space the player rises through before moving across.
+        let rising_box = AABB::new(start_box.min, start_box.max + step);
+        let search_box = AABB::new(rising_box.min.min(raised_box.min), rising_box.max.max(raised_box.max));
+        let nearby_blocks = get_nearby_block_aabbs(&search_box, world).solid;
+        let blocked = nearby_blocks
+            .iter()
+            .any(|block_box| rising_box.intersects(block_box) || raised_box.intersects(block_box));
+        if blocked {
+            return false;
+        }
+
+        // Settle onto the highest block top under the raised box that is above the start.
+        let floor_y = nearby_blocks
+            .iter()
+            .filter(|block_box| {
+                block_box.min.x < raised_box.max.x && block_box.max.x > raised_box.min.x
+                    && block_box.min.z < raised_box.max.z && block_box.max.z > raised_box.min.z
+                    && block_box.max.y > start_box.min.y
+                    && block_box.max.y <= raised_box.min.y
+            })
+            .map(|block_box| block_box.max.y)
+            .fold(f32::NEG_INFINITY, f32::max);
+        if floor_y == f32::NEG_INFINITY {
+            return false;
+        }
+        let step_up = floor_y - self.local_bounding_box.min.y + 0.0001 - start.y;
+        self.position = start + horizontal_move + Vec3::new(0.0, step_up, 0.0);
+        self.step_camera_offset -= step_up;
+        true
+    }
+
+    // Sneaking follows the key, but standing back up waits until there is room above; sprinting
+    // starts with the sprint key (or a double-tapped forward key, see `State::input`) and ends
+    // when the player stops moving forward or sneaks.
+    // Flying players never crouch: sneak is what flies them down.
+    fn update_stance(&mut self, world: &World) {
+        if self.movement_mode == MovementMode::Spectator {
+            self.sneaking = false;
+        } else if self.movement_intention.sneak && self.movement_mode == MovementMode::Walking {
+            self.sneaking = true;
+        } else if self.sneaking {
+            let mut standing_box = self.get_world_bounding_box();
+            standing_box.max.y = self.position.y + PLAYER_HEIGHT;
+            let blocked = get_nearby_block_aabbs(&standing_box, world)
+                .solid
+                .iter()
+                .any(|block_box| standing_box.intersects(block_box));
+            self.sneaking = blocked;
+        }
+        self.local_bounding_box.max.y = if self.sneaking {
+            PLAYER_SNEAK_HEIGHT
+        } else {
+            PLAYER_HEIGHT
+        };
+
+        if self.movement_intention.sprint {
+            self.sprinting = true;
+        }
+        if !self.movement_intention.forward || self.sneaking {
+            self.sprinting = false;
+        }
+    }
+
+    // Shortens a horizontal move until the player would still have ground under it, so a
+    // sneaking player stops at block edges instead of walking off them.
+    fn clamp_to_edge(&self, mut desired_move: Vec3, world: &World) -> Vec3 {
+        const SHRINK_STEP: f32 = 0.05;
+        let has_ground_under = |offset: Vec3| {
+            let mut probe = self.get_world_bounding_box();
+            probe.min += offset - Vec3::new(0.0, SNEAK_EDGE_PROBE_DEPTH, 0.0);
+            probe.max += offset - Vec3::new(0.0, SNEAK_EDGE_PROBE_DEPTH, 0.0);
+            get_nearby_block_aabbs(&probe, world)
+                .solid
+                .iter()
+                .any(|block_box| probe.intersects(block_box))
+        };
+        while desired_move != Vec3::ZERO && !has_ground_under(desired_move) {
+            // Each step moves the offset towards zero without overshooting it.
+            desired_move = desired_move - desired_move.clamp_length_max(SHRINK_STEP);
+        }
+        desired_move
+    }
+
+    // Double-tapping jump switches between walking and flying. Spectators stay spectators.
+    pub fn toggle_flying(&mut self) {
+        self.movement_mode = match self.movement_mode {
+            MovementMode::Walking => MovementMode::Flying,
+            MovementMode::Flying => MovementMode::Walking,
+            MovementMode::Spectator => MovementMode::Spectator,
+        };
+    }
+
+    // Leaving spectator mode keeps the player flying, so it does not drop out of wherever it
+    // has flown to (possibly inside terrain).
+    pub fn toggle_spectator(&mut self) {
+        self.movement_mode = match self.movement_mode {
+            MovementMode::Spectator => MovementMode::Flying,
+            MovementMode::Walking | MovementMode::Flying => MovementMode::Spectator,
+        };
+    }
+
+    pub fn eye_height(&self) -> f32 {
+        if self.sneaking { PLAYER_SNEAK_EYE_HEIGHT } else { PLAYER_EYE_HEIGHT }
+    }
+
+    // Fraction (0 to 1) of the player's height below the highest fluid surface it overlaps.
+    fn submersion(&self, nearby_blocks: &NearbyBlocks) -> f32 {
+        let player_world_box = self.get_world_bounding_box();
+        let surface_y = nearby_blocks
+            .volumes_of(VolumeKind::Fluid)
+            .filter(|volume| player_world_box.intersects(volume))
+            .map(|volume| volume.max.y)
+            .fold(f32::NEG_INFINITY, f32::max);
+        ((surface_y - player_world_box.min.y) / (player_world_box.max.y - player_world_box.min.y)).clamp(0.0, 1.0)
+    }
+
+    // Position between the last two physics ticks; `alpha` 0 is the previous tick, 1 the latest.
+    pub fn interpolated_position(&self, alpha: f32) -> Vec3 {
+        self.previous_position.lerp(self.position, alpha)
+    }
+
+    // Where the camera sits between the last two ticks, including the easing after a step up.
+    pub fn interpolated_eye_position(&self, alpha: f32) -> Vec3 {
+        let step_camera_offset = self.previous_step_camera_offset
+            + (self.step_camera_offset - self.previous_step_camera_offset) * alpha;
+        self.interpolated_position(alpha) + Vec3::new(0.0, self.eye_height() + step_camera_offset, 0.0)
+    }
+
     pub fn get_world_bounding_box(&self) -> AABB {
+        self.bounding_box_at(self.position)
+    }
+
+    fn bounding_box_at(&self, position: Vec3) -> AABB {
         AABB {
-            min: self.position + self.local_bounding_box.min,
-            max: self.position + self.local_bounding_box.max,
+            min: position + self.local_bounding_box.min,
+            max: position + self.local_bounding_box.max,
         }
     }
 }
 
-// Helper function to get AABBs of solid blocks near the player
+// What a non-solid block the player can overlap is made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VolumeKind {
+    Fluid,
+    Other, // e.g. torches; nothing reacts to these yet
+}
+
+// Blocks near the player: solid ones to collide with, and every other non-air block as a
+// volume the player can be inside of (fluids only up to their surface).
+#[derive(Debug, Default)]
+struct NearbyBlocks {
+    solid: Vec<AABB>,
+    non_solid: Vec<(AABB, VolumeKind)>,
+}
+
+impl NearbyBlocks {
+    fn volumes_of(&self, kind: VolumeKind) -> impl Iterator<Item = &AABB> {
+        self.non_solid
+            .iter()
+            .filter(move |(_, volume_kind)| *volume_kind == kind)
+            .map(|(volume, _)| volume)
+    }
+}
+
+// Helper function to get AABBs of solid blocks and non-solid volumes near the player
 // This function now queries the World instead of a single Chunk.
-fn get_nearby_block_aabbs(player_world_box: &AABB, world: &World) -> Vec<AABB> {
-    let mut nearby_blocks = Vec::new();
+fn get_nearby_block_aabbs(player_world_box: &AABB, world: &World) -> NearbyBlocks {
+    let mut nearby_blocks = NearbyBlocks::default();
 
     // Determine the range of world block coordinates that the player's AABB might overlap.
     // Add a small buffer (e.g., 1 block) just in case.
@@ -238,21 +518,33 @@ fn get_nearby_block_aabbs(player_world_box: &AABB, world: &World) -> Vec<AABB> {
     for world_bx in min_world_block_x..max_world_block_x {
         for world_by in min_world_block_y..max_world_block_y {
             for world_bz in min_world_block_z..max_world_block_z {
-                // Y coordinates for blocks are absolute from 0 upwards.
+                // Y coordinates for blocks are absolute, from MIN_Y upwards.
                 // We can pre-filter Y here if it's outside the general world height,
                 // though world.get_block_at_world also handles Y bounds.
-                if world_by < 0 || world_by >= crate::chunk::CHUNK_HEIGHT as i32 {
+                if !(crate::chunk::MIN_Y..crate::chunk::MAX_Y).contains(&world_by) {
                     continue;
                 }
 
                 // Use world.get_block_at_world to get block data
                 // This method handles chunk boundaries internally.
                 if let Some(block) = world.get_block_at_world(world_bx as f32, world_by as f32, world_bz as f32) {
+                    // The AABB for the block should be in world coordinates
+                    let block_min_corner = Vec3::new(world_bx as f32, world_by as f32, world_bz as f32);
                     if block.is_solid() {
-                        // The AABB for the block should be in world coordinates
-                        let block_min_corner = Vec3::new(world_bx as f32, world_by as f32, world_bz as f32);
                         let block_max_corner = Vec3::new(world_bx as f32 + 1.0, world_by as f32 + 1.0, world_bz as f32 + 1.0);
-                        nearby_blocks.push(AABB::new(block_min_corner, block_max_corner));
+                        nearby_blocks.solid.push(AABB::new(block_min_corner, block_max_corner));
+                    } else if block.is_fluid() {
+                        // Fluid with more of the same on top fills its block; otherwise it
+                        // reaches up to its surface.
+                        let fluid_above = world
+                            .get_block_at_world(world_bx as f32, world_by as f32 + 1.0, world_bz as f32)
+                            .is_some_and(|above| above.block_type == block.block_type);
+                        let height = if fluid_above { 1.0 } else { block.fluid_height() };
+                        let block_max_corner = Vec3::new(world_bx as f32 + 1.0, world_by as f32 + height, world_bz as f32 + 1.0);
+                        nearby_blocks.non_solid.push((AABB::new(block_min_corner, block_max_corner), VolumeKind::Fluid));
+                    } else if block.block_type != BlockType::AIR {
+                        let block_max_corner = Vec3::new(world_bx as f32 + 1.0, world_by as f32 + 1.0, world_bz as f32 + 1.0);
+                        nearby_blocks.non_solid.push((AABB::new(block_min_corner, block_max_corner), VolumeKind::Other));
                     }
                 }
             }
@@ -260,3 +552,246 @@ fn get_nearby_block_aabbs(player_world_box: &AABB, world: &World) -> Vec<AABB> {
     }
     nearby_blocks
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockType;
+    use crate::worldgen::flat::{FLAT_SURFACE_Y, FlatWorldGenerator};
+    use glam::IVec3;
+    use std::sync::Arc;
+
+    const POOL_DEPTH: i32 = 6;
+
+    // Flat terrain with a pool of still water, POOL_DEPTH blocks deep, dug into the middle of
+    // chunk (0, 0).
+    fn world_with_pool() -> World {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        world.get_or_create_chunk(0, 0);
+        for x in 4..12 {
+            for y in FLAT_SURFACE_Y - POOL_DEPTH + 1..=FLAT_SURFACE_Y {
+                for z in 4..12 {
+                    world.set_block(IVec3::new(x, y, z), BlockType::WATER).unwrap();
+                }
+            }
+        }
+        world
+    }
+
+    // A player standing on the bottom of the pool.
+    fn player_in_pool() -> Player {
+        let feet_y = (FLAT_SURFACE_Y - POOL_DEPTH + 1) as f32;
+        Player::new(Vec3::new(8.0, feet_y, 8.0), 0.0, 0.0, 0.0)
+    }
+
+    fn simulate(player: &mut Player, world: &World, seconds: f32) {
+        const DT: f32 = 1.0 / 60.0;
+        for _ in 0..(seconds / DT) as usize {
+            player.update_physics_and_collision(DT, world);
+        }
+    }
+
+    // Flat terrain with a one block high stone platform over x 4..8, z 4..12, and a player on
+    // it walking towards its +X edge.
+    fn player_on_platform(world: &mut World) -> Player {
+        world.get_or_create_chunk(0, 0);
+        for x in 4..8 {
+            for z in 4..12 {
+                world.set_block(IVec3::new(x, FLAT_SURFACE_Y + 1, z), BlockType::STONE).unwrap();
+            }
+        }
+        let mut player = Player::new(Vec3::new(6.5, (FLAT_SURFACE_Y + 2) as f32, 8.0), 0.0, 0.0, 0.0);
+        player.movement_intention.forward = true;
+        player
+    }
+
+    #[test]
+    fn test_sneaking_stops_at_block_edges() {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        let mut player = player_on_platform(&mut world);
+        player.movement_intention.sneak = true;
+        simulate(&mut player, &world, 3.0);
+
+        assert!(player.sneaking);
+        assert!(player.on_ground);
+        assert!(player.position.y >= (FLAT_SURFACE_Y + 2) as f32);
+        // The player leans out over the edge but its box still rests on the platform.
+        assert!(player.position.x > 8.0);
+        assert!(player.position.x < 8.0 + PLAYER_HALF_WIDTH);
+        assert_eq!(player.eye_height(), PLAYER_SNEAK_EYE_HEIGHT);
+        assert_eq!(player.get_world_bounding_box().max.y - player.position.y, PLAYER_SNEAK_HEIGHT);
+    }
+
+    #[test]
+    fn test_walking_goes_over_block_edges() {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        let mut player = player_on_platform(&mut world);
+        simulate(&mut player, &world, 3.0);
+        assert!(player.position.y < (FLAT_SURFACE_Y + 2) as f32);
+    }
+
+    #[test]
+    fn test_sprinting_is_faster_and_stops_with_forward() {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        let mut walking = player_on_platform(&mut world);
+        walking.position.x = 4.5;
+        let mut sprinting = player_on_platform(&mut world);
+        sprinting.position.x = 4.5;
+        sprinting.movement_intention.sprint = true;
+        simulate(&mut walking, &world, 0.5);
+        simulate(&mut sprinting, &world, 0.5);
+
+        assert!(sprinting.sprinting);
+        let walked = walking.position.x - 4.5;
+        let sprinted = sprinting.position.x - 4.5;
+        assert!((sprinted / walked - SPRINT_SPEED_MULTIPLIER).abs() < 0.01);
+
+        sprinting.movement_intention.sprint = false;
+        sprinting.movement_intention.forward = false;
+        simulate(&mut sprinting, &world, 0.1);
+        assert!(!sprinting.sprinting);
+    }
+
+    #[test]
+    fn test_flying_hovers_and_lands_when_flying_down() {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        world.get_or_create_chunk(0, 0);
+        let hover_y = (FLAT_SURFACE_Y + 5) as f32;
+        let mut player = Player::new(Vec3::new(8.0, hover_y, 8.0), 0.0, 0.0, 0.0);
+        player.toggle_flying();
+        simulate(&mut player, &world, 1.0);
+        assert_eq!(player.movement_mode, MovementMode::Flying);
+        assert_eq!(player.position.y, hover_y);
+
+        player.movement_intention.sneak = true;
+        simulate(&mut player, &world, 2.0);
+        assert_eq!(player.movement_mode, MovementMode::Walking);
+        assert!(player.on_ground);
+        // Once on the ground the held sneak key crouches instead.
+        assert!(player.sneaking);
+    }
+
+    #[test]
+    fn test_spectator_passes_through_blocks() {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        world.get_or_create_chunk(0, 0);
+        let mut player = Player::new(Vec3::new(8.0, (FLAT_SURFACE_Y + 1) as f32, 8.0), 0.0, 0.0, 0.0);
+        player.toggle_spectator();
+        player.movement_intention.sneak = true;
+        simulate(&mut player, &world, 1.0);
+        assert!(player.position.y < (FLAT_SURFACE_Y - 3) as f32);
+        assert!(!player.on_ground);
+
+        // Leaving spectator mode underground keeps the player flying rather than stuck.
+        player.toggle_spectator();
+        assert_eq!(player.movement_mode, MovementMode::Flying);
+        player.toggle_flying();
+        assert_eq!(player.movement_mode, MovementMode::Walking);
+    }
+
+    // A player (with the default step height) on flat ground at x 2.5 walking +X into the one
+    // block high platform of `player_on_platform`, which starts at x 4.
+    fn player_before_ledge(world: &mut World) -> Player {
+        let mut player = player_on_platform(world);
+        player.position = Vec3::new(2.5, (FLAT_SURFACE_Y + 1) as f32, 8.0);
+        player
+    }
+
+    #[test]
+    fn test_ledges_higher_than_the_step_height_block_the_player() {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        let mut player = player_before_ledge(&mut world);
+        // Raise the platform's edge to two blocks.
+        for z in 4..12 {
+            world.set_block(IVec3::new(4, FLAT_SURFACE_Y + 2, z), BlockType::STONE).unwrap();
+        }
+        simulate(&mut player, &world, 1.0);
+        assert!(player.position.x < 4.0 - PLAYER_HALF_WIDTH + 0.001);
+        assert!(player.position.y < (FLAT_SURFACE_Y + 2) as f32);
+    }
+
+    #[test]
+    fn test_player_steps_up_single_block_ledges() {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        let mut player = player_before_ledge(&mut world);
+        let eye_before_step = player.interpolated_eye_position(1.0).y;
+        let mut stepped = false;
+        for _ in 0..60 {
+            player.update_physics_and_collision(1.0 / 60.0, &world);
+            if !stepped && player.position.y > (FLAT_SURFACE_Y + 2) as f32 {
+                stepped = true;
+                // The camera starts from where it was and eases up after the player.
+                let eye = player.interpolated_eye_position(1.0).y;
+                assert!(eye - eye_before_step < 0.1, "camera jumped by {}", eye - eye_before_step);
+            }
+        }
+        assert!(stepped);
+        assert!(player.on_ground);
+        assert!(player.position.x > 4.5);
+        let eye = player.interpolated_eye_position(1.0).y;
+        assert!((eye - (player.position.y + PLAYER_EYE_HEIGHT)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fluid_volumes_reach_up_to_the_surface() {
+        let world = world_with_pool();
+        let player = Player::new(Vec3::new(8.0, (FLAT_SURFACE_Y - 1) as f32, 8.0), 0.0, 0.0, 0.0);
+        let nearby = get_nearby_block_aabbs(&player.get_world_bounding_box(), &world);
+        let surface_y = nearby.volumes_of(VolumeKind::Fluid).map(|volume| volume.max.y).fold(f32::MIN, f32::max);
+        assert_eq!(surface_y, FLAT_SURFACE_Y as f32 + 8.0 / 9.0);
+        // Water below the surface fills its whole block.
+        assert!(nearby.volumes_of(VolumeKind::Fluid).any(|volume| volume.max.y == FLAT_SURFACE_Y as f32));
+        assert!(nearby.solid.is_empty());
+    }
+
+    #[test]
+    fn test_non_solid_blocks_other_than_fluids_are_reported() {
+        let mut world = World::with_generator(0, Arc::new(FlatWorldGenerator));
+        world.get_or_create_chunk(0, 0);
+        let torch = IVec3::new(8, FLAT_SURFACE_Y + 1, 8);
+        let torch_type = crate::block_registry::registry().by_name("torch").unwrap();
+        world.set_block(torch, torch_type).unwrap();
+        let player = Player::new(Vec3::new(8.5, (FLAT_SURFACE_Y + 1) as f32, 8.5), 0.0, 0.0, 0.0);
+        let nearby = get_nearby_block_aabbs(&player.get_world_bounding_box(), &world);
+
+        let others: Vec<&AABB> = nearby.volumes_of(VolumeKind::Other).collect();
+        assert_eq!(others.len(), 1);
+        assert_eq!(others[0].min, torch.as_vec3());
+        assert_eq!(nearby.volumes_of(VolumeKind::Fluid).count(), 0);
+        assert!(!nearby.solid.iter().any(|block_box| block_box.min == torch.as_vec3()));
+    }
+
+    #[test]
+    fn test_player_floats_up_and_settles_at_the_surface() {
+        let world = world_with_pool();
+        let mut player = player_in_pool();
+        simulate(&mut player, &world, 15.0);
+
+        assert!(player.in_fluid);
+        let expected_submersion = FLUID_GRAVITY / FLUID_BUOYANCY;
+        let surface_y = FLAT_SURFACE_Y as f32 + 8.0 / 9.0;
+        let submersion = (surface_y - player.position.y) / PLAYER_HEIGHT;
+        assert!(
+            (submersion - expected_submersion).abs() < 0.05,
+            "submersion {} instead of {}",
+            submersion,
+            expected_submersion
+        );
+        assert!(player.velocity.y.abs() < 0.1);
+    }
+
+    #[test]
+    fn test_holding_jump_swims_up_faster_than_floating() {
+        let world = world_with_pool();
+        let start_y = player_in_pool().position.y;
+
+        let mut floating = player_in_pool();
+        simulate(&mut floating, &world, 1.0);
+        let mut swimming = player_in_pool();
+        swimming.movement_intention.jump = true;
+        simulate(&mut swimming, &world, 1.0);
+
+        assert!(floating.position.y > start_y);
+        assert!(swimming.position.y - start_y > 2.0 * (floating.position.y - start_y));
+    }
+}
@@ -1,7 +1,6 @@
 use glam::{IVec3, Vec3};
 use crate::player::Player;
 use crate::world::World;
-use crate::physics::PLAYER_EYE_HEIGHT;
 use crate::block::BlockType;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,7 +19,7 @@ pub fn cast_ray(
     world: &World,
     max_distance: f32,
 ) -> Option<(IVec3, BlockFace)> {
-    let eye_position = player.position + Vec3::new(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let eye_position = player.position + Vec3::new(0.0, player.eye_height(), 0.0);
     let ray_direction = Vec3::new(
         player.yaw.cos() * player.pitch.cos(),
         player.pitch.sin(),
@@ -74,7 +73,7 @@ pub fn cast_ray(
         current_voxel_coord.y as f32,
         current_voxel_coord.z as f32,
     ) {
-        if block.block_type != BlockType::Air {
+        if block.block_type != BlockType::AIR {
             // Cannot select the block the eye is inside. This case is tricky.
             // For now, we assume the first step will take us out of it.
             // Or, we could determine a face based on ray direction from center of this block.
@@ -124,7 +123,8 @@ pub fn cast_ray(
             current_voxel_coord.y as f32,
             current_voxel_coord.z as f32,
         ) {
-            if block.block_type != BlockType::Air { // Found a solid block
+            // Fluids can't be targeted; the ray passes through them like air.
+            if block.block_type != BlockType::AIR && !block.is_fluid() { // Found a solid block
                 return Some((current_voxel_coord, last_face));
             }
         } else {
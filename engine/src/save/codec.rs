@@ -0,0 +1,96 @@
+// engine/src/save/codec.rs
+
+use std::io;
+
+// Little-endian byte writer for the binary save formats.
+#[derive(Default)]
+pub struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn put_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn put_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+}
+
+// Reads what `ByteWriter` wrote. Running out of bytes is an `InvalidData` error, so a
+// truncated file is reported rather than read as zeros.
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        ByteReader { bytes }
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(invalid_data("Unexpected end of data"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("Took exactly N bytes"))
+    }
+
+    pub fn get_u8(&mut self) -> io::Result<u8> {
+        Ok(self.take_array::<1>()?[0])
+    }
+
+    pub fn get_u16(&mut self) -> io::Result<u16> {
+        self.take_array().map(u16::from_le_bytes)
+    }
+
+    pub fn get_u32(&mut self) -> io::Result<u32> {
+        self.take_array().map(u32::from_le_bytes)
+    }
+
+    pub fn get_u64(&mut self) -> io::Result<u64> {
+        self.take_array().map(u64::from_le_bytes)
+    }
+
+    pub fn get_i32(&mut self) -> io::Result<i32> {
+        self.take_array().map(i32::from_le_bytes)
+    }
+}
+
+pub fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
@@ -0,0 +1,125 @@
+// engine/src/save/level.rs
+
+use crate::block_registry::registry;
+use crate::player::MovementMode;
+use crate::ui::item::{ItemStack, ItemType};
+use serde::{Deserialize, Serialize};
+
+pub const LEVEL_FORMAT_VERSION: u32 = 1;
+
+// Everything about a world that is not chunk data. Stored as JSON so it can be inspected
+// and edited by hand.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelData {
+    pub version: u32,
+    pub seed: u64,
+    pub player: PlayerData,
+    pub inventory: Vec<Option<SavedItemStack>>,
+    pub hotbar: Vec<Option<SavedItemStack>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerData {
+    pub position: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+    // Missing from levels saved before flight existed; those players walk.
+    #[serde(default)]
+    pub movement_mode: MovementMode,
+}
+
+// Blocks are saved by registry name rather than id, so stacks survive ids being reshuffled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedItemStack {
+    pub block: String,
+    pub count: u8,
+}
+
+impl SavedItemStack {
+    pub fn from_stack(stack: &ItemStack) -> Self {
+        let ItemType::Block(block_type) = stack.item_type;
+        SavedItemStack {
+            block: registry().get(block_type).name.clone(),
+            count: stack.count,
+        }
+    }
+
+    // None if the block no longer exists.
+    pub fn to_stack(&self) -> Option<ItemStack> {
+        let block_type = registry().by_name(&self.block)?;
+        Some(ItemStack::new(ItemType::Block(block_type), self.count))
+    }
+}
+
+pub fn save_slots(slots: &[Option<ItemStack>]) -> Vec<Option<SavedItemStack>> {
+    slots
+        .iter()
+        .map(|slot| slot.as_ref().map(SavedItemStack::from_stack))
+        .collect()
+}
+
+// Fills `slots` from saved ones. Slots the save does not cover are emptied.
+pub fn restore_slots(saved: &[Option<SavedItemStack>], slots: &mut [Option<ItemStack>]) {
+    for (index, slot) in slots.iter_mut().enumerate() {
+        *slot = saved
+            .get(index)
+            .and_then(|saved| saved.as_ref())
+            .and_then(SavedItemStack::to_stack);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockType;
+
+    #[test]
+    fn test_level_data_round_trips_through_json() {
+        let hotbar = [
+            Some(ItemStack::new(ItemType::Block(BlockType::DIRT), 64)),
+            None,
+            Some(ItemStack::new(ItemType::Block(BlockType::OAK_LOG), 3)),
+        ];
+        let level = LevelData {
+            version: LEVEL_FORMAT_VERSION,
+            seed: 1234,
+            player: PlayerData {
+                position: [1.5, 70.0, -3.25],
+                yaw: 0.5,
+                pitch: -0.25,
+                movement_mode: MovementMode::Spectator,
+            },
+            inventory: vec![None; 4],
+            hotbar: save_slots(&hotbar),
+        };
+
+        let json = serde_json::to_string(&level).unwrap();
+        let loaded: LevelData = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, level);
+
+        let mut restored = [None; 4];
+        restore_slots(&loaded.hotbar, &mut restored);
+        assert_eq!(
+            restored.map(|slot| slot.map(|stack| (stack.item_type, stack.count))),
+            [
+                Some((ItemType::Block(BlockType::DIRT), 64)),
+                None,
+                Some((ItemType::Block(BlockType::OAK_LOG), 3)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_level_without_movement_mode_loads_as_walking() {
+        let json = r#"{
+            "version": 1,
+            "seed": 7,
+            "player": { "position": [0.5, 80.0, 0.5], "yaw": 0.0, "pitch": 0.0 },
+            "inventory": [],
+            "hotbar": []
+        }"#;
+        let level: LevelData = serde_json::from_str(json).unwrap();
+        assert_eq!(level.player.movement_mode, MovementMode::Walking);
+    }
+}
@@ -0,0 +1,143 @@
+// engine/src/save/mod.rs
+
+pub mod codec;
+pub mod level;
+pub mod placements;
+pub mod region;
+
+use crate::chunk::Chunk;
+use crate::world::PendingPlacements;
+use codec::invalid_data;
+use level::LevelData;
+use region::RegionFile;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const LEVEL_FILE: &str = "level.json";
+const PLACEMENTS_FILE: &str = "placements.dat";
+const REGION_DIR: &str = "region";
+
+// A world directory on disk: `level.json`, pending structure blocks and one region file
+// per 32×32 chunks. Shared between the main thread and the chunk workers; region files are
+// only touched while holding the lock.
+pub struct WorldSave {
+    dir: PathBuf,
+    regions: Mutex<HashMap<(i32, i32), RegionFile>>,
+}
+
+impl WorldSave {
+    // Opens a world directory, creating it if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(dir.join(REGION_DIR))?;
+        Ok(WorldSave {
+            dir,
+            regions: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn region_path(&self, region: (i32, i32)) -> PathBuf {
+        self.dir
+            .join(REGION_DIR)
+            .join(format!("r.{}.{}.region", region.0, region.1))
+    }
+
+    // Writes through a temporary file, so a crash never leaves half a file behind.
+    fn write_atomically(&self, name: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.dir.join(name);
+        let temp_path = self.dir.join(format!("{}.tmp", name));
+        fs::write(&temp_path, bytes)?;
+        fs::rename(temp_path, path)
+    }
+
+    fn read_if_exists(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.dir.join(name)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    // Runs `f` on the region file holding `coord`. Regions that do not exist yet are only
+    // created if `create` is set; otherwise `f` is not called and None is returned.
+    fn with_region<R>(
+        &self,
+        coord: (i32, i32),
+        create: bool,
+        f: impl FnOnce(&mut RegionFile, usize) -> io::Result<R>,
+    ) -> io::Result<Option<R>> {
+        let (region, slot) = region::region_coord(coord);
+        let mut regions = self
+            .regions
+            .lock()
+            .map_err(|_| io::Error::other("Region lock poisoned"))?;
+        let region_file = match regions.entry(region) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let path = self.region_path(region);
+                if !create && !path.exists() {
+                    return Ok(None);
+                }
+                entry.insert(RegionFile::open(&path)?)
+            }
+        };
+        f(region_file, slot).map(Some)
+    }
+
+    pub fn load_level(&self) -> io::Result<Option<LevelData>> {
+        let Some(bytes) = self.read_if_exists(LEVEL_FILE)? else {
+            return Ok(None);
+        };
+        let level: LevelData = serde_json::from_slice(&bytes).map_err(io::Error::other)?;
+        if level.version != level::LEVEL_FORMAT_VERSION {
+            return Err(invalid_data("Unsupported level format version"));
+        }
+        Ok(Some(level))
+    }
+
+    pub fn save_level(&self, level: &LevelData) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(level).map_err(io::Error::other)?;
+        self.write_atomically(LEVEL_FILE, &json)
+    }
+
+    // The saved copy of a chunk, or None if it was never saved.
+    pub fn load_chunk(&self, coord: (i32, i32)) -> io::Result<Option<Chunk>> {
+        let Some(Some(bytes)) = self.with_region(coord, false, |region, slot| region.read(slot))?
+        else {
+            return Ok(None);
+        };
+        let chunk = Chunk::decode(&bytes)?;
+        if chunk.coord != coord {
+            return Err(invalid_data(format!(
+                "Region slot for chunk {:?} holds chunk {:?}",
+                coord, chunk.coord
+            )));
+        }
+        Ok(Some(chunk))
+    }
+
+    pub fn save_chunk(&self, chunk: &Chunk) -> io::Result<()> {
+        let bytes = chunk.encode();
+        self.with_region(chunk.coord, true, |region, slot| region.write(slot, &bytes))?;
+        Ok(())
+    }
+
+    pub fn load_pending_placements(&self) -> io::Result<PendingPlacements> {
+        match self.read_if_exists(PLACEMENTS_FILE)? {
+            Some(bytes) => placements::decode(&bytes),
+            None => Ok(PendingPlacements::new()),
+        }
+    }
+
+    pub fn save_pending_placements(&self, pending: &PendingPlacements) -> io::Result<()> {
+        self.write_atomically(PLACEMENTS_FILE, &placements::encode(pending))
+    }
+}
@@ -0,0 +1,62 @@
+// engine/src/save/placements.rs
+
+use super::codec::{ByteReader, ByteWriter, invalid_data};
+use crate::block::BlockType;
+use crate::world::PendingPlacements;
+use crate::worldgen::structures::BlockPlacement;
+use glam::IVec3;
+use std::io;
+
+const PLACEMENTS_FORMAT_VERSION: u8 = 1;
+
+// Structure blocks still waiting for their target chunk, grouped by target and source chunk.
+pub fn encode(pending: &PendingPlacements) -> Vec<u8> {
+    let mut writer = ByteWriter::new();
+    writer.put_u8(PLACEMENTS_FORMAT_VERSION);
+    writer.put_u32(pending.len() as u32);
+    for (target, by_source) in pending {
+        writer.put_i32(target.0);
+        writer.put_i32(target.1);
+        writer.put_u32(by_source.len() as u32);
+        for (source, placements) in by_source {
+            writer.put_i32(source.0);
+            writer.put_i32(source.1);
+            writer.put_u32(placements.len() as u32);
+            for placement in placements {
+                writer.put_i32(placement.position.x);
+                writer.put_i32(placement.position.y);
+                writer.put_i32(placement.position.z);
+                writer.put_u16(placement.block_type.0);
+                writer.put_u32(placement.tree_id.unwrap_or(0));
+            }
+        }
+    }
+    writer.into_bytes()
+}
+
+pub fn decode(bytes: &[u8]) -> io::Result<PendingPlacements> {
+    let mut reader = ByteReader::new(bytes);
+    if reader.get_u8()? != PLACEMENTS_FORMAT_VERSION {
+        return Err(invalid_data("Unsupported placements format version"));
+    }
+    let mut pending = PendingPlacements::new();
+    for _ in 0..reader.get_u32()? {
+        let target = (reader.get_i32()?, reader.get_i32()?);
+        let by_source = pending.entry(target).or_default();
+        for _ in 0..reader.get_u32()? {
+            let source = (reader.get_i32()?, reader.get_i32()?);
+            let placements = by_source.entry(source).or_default();
+            for _ in 0..reader.get_u32()? {
+                placements.push(BlockPlacement {
+                    position: IVec3::new(reader.get_i32()?, reader.get_i32()?, reader.get_i32()?),
+                    block_type: BlockType(reader.get_u16()?),
+                    tree_id: Some(reader.get_u32()?).filter(|&tree_id| tree_id != 0),
+                });
+            }
+        }
+    }
+    if !reader.is_at_end() {
+        return Err(invalid_data("Trailing bytes after placement data"));
+    }
+    Ok(pending)
+}
@@ -0,0 +1,242 @@
+// engine/src/save/region.rs
+
+use super::codec::invalid_data;
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+// A region file holds REGION_SIZE × REGION_SIZE chunks. It starts with an offset table of
+// one entry per chunk (first sector, sector count; both 0 while the chunk was never saved),
+// followed by the chunks themselves, each starting on a sector boundary as
+// `[compressed length: u32][compression: u8][zlib data]`.
+pub const REGION_SIZE: i32 = 32;
+const CHUNKS_PER_REGION: usize = (REGION_SIZE * REGION_SIZE) as usize;
+const SECTOR_BYTES: usize = 4096;
+const ENTRY_BYTES: usize = 8;
+const HEADER_SECTORS: u32 = (CHUNKS_PER_REGION * ENTRY_BYTES).div_ceil(SECTOR_BYTES) as u32;
+const CHUNK_HEADER_BYTES: usize = 5;
+const COMPRESSION_ZLIB: u8 = 1;
+
+// Region that contains a chunk, and the chunk's slot within it.
+pub fn region_coord(chunk_coord: (i32, i32)) -> ((i32, i32), usize) {
+    let region = (
+        chunk_coord.0.div_euclid(REGION_SIZE),
+        chunk_coord.1.div_euclid(REGION_SIZE),
+    );
+    let slot = chunk_coord.0.rem_euclid(REGION_SIZE) as usize * REGION_SIZE as usize
+        + chunk_coord.1.rem_euclid(REGION_SIZE) as usize;
+    (region, slot)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Entry {
+    first_sector: u32,
+    sector_count: u32,
+}
+
+impl Entry {
+    // Checks that a used entry points past the offset table and inside a file of
+    // `file_len` bytes, before anything is allocated or read from its sectors.
+    fn check(&self, file_len: u64) -> io::Result<()> {
+        if self.sector_count == 0 {
+            return Ok(());
+        }
+        let end = (self.first_sector as u64 + self.sector_count as u64) * SECTOR_BYTES as u64;
+        if self.first_sector < HEADER_SECTORS || end > file_len {
+            return Err(invalid_data("Region file entry points outside the file"));
+        }
+        Ok(())
+    }
+}
+
+pub struct RegionFile {
+    file: File,
+    entries: Vec<Entry>,
+}
+
+impl RegionFile {
+    // Opens a region file, creating an empty one if it does not exist yet.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let header_bytes = HEADER_SECTORS as usize * SECTOR_BYTES;
+        if file.metadata()?.len() < header_bytes as u64 {
+            file.set_len(header_bytes as u64)?;
+        }
+
+        let mut header = vec![0; CHUNKS_PER_REGION * ENTRY_BYTES];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut header)?;
+        let entries: Vec<Entry> = header
+            .chunks_exact(ENTRY_BYTES)
+            .map(|entry| Entry {
+                first_sector: u32::from_le_bytes(entry[0..4].try_into().unwrap()),
+                sector_count: u32::from_le_bytes(entry[4..8].try_into().unwrap()),
+            })
+            .collect();
+        let file_len = file.metadata()?.len();
+        for entry in &entries {
+            entry.check(file_len)?;
+        }
+        Ok(RegionFile { file, entries })
+    }
+
+    // The uncompressed data saved for a slot, if any.
+    pub fn read(&mut self, slot: usize) -> io::Result<Option<Vec<u8>>> {
+        let entry = self.entries[slot];
+        if entry.sector_count == 0 {
+            return Ok(None);
+        }
+        entry.check(self.file.metadata()?.len())?;
+        let mut stored = vec![0; entry.sector_count as usize * SECTOR_BYTES];
+        self.file.seek(SeekFrom::Start(
+            entry.first_sector as u64 * SECTOR_BYTES as u64,
+        ))?;
+        self.file.read_exact(&mut stored)?;
+
+        let length = u32::from_le_bytes(stored[0..4].try_into().unwrap()) as usize;
+        if stored[4] != COMPRESSION_ZLIB || CHUNK_HEADER_BYTES + length > stored.len() {
+            return Err(invalid_data("Corrupt chunk header in region file"));
+        }
+        let mut data = Vec::new();
+        ZlibDecoder::new(&stored[CHUNK_HEADER_BYTES..CHUNK_HEADER_BYTES + length])
+            .read_to_end(&mut data)?;
+        Ok(Some(data))
+    }
+
+    // Compresses and stores `data` in a slot, reusing its old sectors when it still fits
+    // and otherwise moving it to the first free run of sectors.
+    pub fn write(&mut self, slot: usize, data: &[u8]) -> io::Result<()> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+
+        let mut stored = Vec::with_capacity(CHUNK_HEADER_BYTES + compressed.len());
+        stored.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        stored.push(COMPRESSION_ZLIB);
+        stored.extend_from_slice(&compressed);
+        let sector_count = stored.len().div_ceil(SECTOR_BYTES) as u32;
+        stored.resize(sector_count as usize * SECTOR_BYTES, 0);
+
+        let old_entry = self.entries[slot];
+        let first_sector = if sector_count <= old_entry.sector_count {
+            old_entry.first_sector
+        } else {
+            self.entries[slot] = Entry::default();
+            self.find_free_sectors(sector_count)
+        };
+
+        self.file
+            .seek(SeekFrom::Start(first_sector as u64 * SECTOR_BYTES as u64))?;
+        self.file.write_all(&stored)?;
+
+        let entry = Entry {
+            first_sector,
+            sector_count,
+        };
+        self.entries[slot] = entry;
+        self.file
+            .seek(SeekFrom::Start((slot * ENTRY_BYTES) as u64))?;
+        self.file.write_all(&entry.first_sector.to_le_bytes())?;
+        self.file.write_all(&entry.sector_count.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn find_free_sectors(&self, sector_count: u32) -> u32 {
+        let mut used: Vec<(u32, u32)> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.sector_count > 0)
+            .map(|entry| (entry.first_sector, entry.first_sector + entry.sector_count))
+            .collect();
+        used.sort_unstable();
+        let mut candidate = HEADER_SECTORS;
+        for (start, end) in used {
+            if start >= candidate + sector_count {
+                break;
+            }
+            candidate = candidate.max(end);
+        }
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_region_path(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("majn-region-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_region_coords_wrap_for_negative_chunks() {
+        assert_eq!(region_coord((0, 0)), ((0, 0), 0));
+        assert_eq!(region_coord((31, 1)), ((0, 0), 31 * 32 + 1));
+        assert_eq!(region_coord((-1, -32)), ((-1, -1), 31 * 32));
+    }
+
+    #[test]
+    fn test_chunks_survive_rewrites_and_reopening() {
+        let path = temp_region_path("rewrite.region");
+        let small = vec![7u8; 100];
+        // Noise-like data that does not compress, so it needs more than one sector.
+        let large: Vec<u8> = (0..20_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+        {
+            let mut region = RegionFile::open(&path).unwrap();
+            assert_eq!(region.read(5).unwrap(), None);
+            region.write(5, &small).unwrap();
+            region.write(6, &small).unwrap();
+            // Slot 5 outgrows its sector and has to move past slot 6.
+            region.write(5, &large).unwrap();
+            region.write(7, &small).unwrap();
+        }
+
+        let mut region = RegionFile::open(&path).unwrap();
+        assert_eq!(region.read(8).unwrap(), None);
+        assert_eq!(region.read(5).unwrap(), Some(large));
+        assert_eq!(region.read(6).unwrap(), Some(small.clone()));
+        assert_eq!(region.read(7).unwrap(), Some(small));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_entries_pointing_outside_the_file_are_invalid_data() {
+        let path = temp_region_path("corrupt.region");
+        let mut region = RegionFile::open(&path).unwrap();
+        region.write(3, &[1, 2, 3]).unwrap();
+        // The chunk's sectors are cut off after the entry was written.
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(HEADER_SECTORS as u64 * SECTOR_BYTES as u64)
+            .unwrap();
+        drop(file);
+        let error = region.read(3).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        drop(region);
+        let error = RegionFile::open(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // An entry asking for an absurd number of sectors.
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(3 * ENTRY_BYTES as u64)).unwrap();
+        file.write_all(&HEADER_SECTORS.to_le_bytes()).unwrap();
+        file.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        drop(file);
+        let error = RegionFile::open(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -8,28 +8,37 @@ var<uniform> camera: CameraUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
-    @location(1) color: vec3<f32>,    // Kept for potential future use (tinting, etc.)
+    @location(1) tint: vec3<f32>,     // Biome colour for grass/foliage, white otherwise
     @location(2) uv: vec2<f32>,       // Texture coordinates
     @location(3) tree_id: u32,      // Tree ID
-    @location(4) sky_light: u32,
+    @location(4) sky_light: f32,
+    @location(5) block_light: f32,  // Light from emissive blocks (torches, lava, ...)
+    @location(6) ao: f32,           // Ambient occlusion: 0 = enclosed corner, 3 = open
+    @location(7) atlas_tile: vec2<f32>, // Top-left corner of the texture in the atlas
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) original_color: vec3<f32>, // Pass original color through
-    @location(1) tex_coords: vec2<f32>,   // Pass UVs to fragment shader
+    @location(0) tint: vec3<f32>,        // Pass biome tint through
+    @location(1) tex_coords: vec2<f32>,   // UVs in blocks, wrapped into the atlas tile
     @location(2) tree_id: u32,           // Pass Tree ID to fragment shader
-    @location(3) @interpolate(flat) sky_light: u32,
+    @location(3) sky_light: f32,
+    @location(4) block_light: f32,
+    @location(5) ao: f32,
+    @location(6) atlas_tile: vec2<f32>,
 };
 
 @vertex
 fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
-    out.original_color = model.color; // Pass through the original vertex color
+    out.tint = model.tint;            // Pass through the biome tint
     out.tex_coords = model.uv;        // Pass through UV coordinates
     out.tree_id = model.tree_id;      // Pass through Tree ID
     out.sky_light = model.sky_light;
+    out.block_light = model.block_light;
+    out.ao = model.ao;
+    out.atlas_tile = model.atlas_tile;
     return out;
 }
 
@@ -41,10 +50,13 @@ fn vs_main(model: VertexInput) -> VertexOutput {
 
 // Input from vertex shader (matches VertexOutput)
 struct FragmentInput {
-    @location(0) original_color: vec3<f32>,
+    @location(0) tint: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
     @location(2) tree_id: u32,
-    @location(3) @interpolate(flat) sky_light: u32,
+    @location(3) sky_light: f32,
+    @location(4) block_light: f32,
+    @location(5) ao: f32,
+    @location(6) atlas_tile: vec2<f32>,
 }
 
 // Calculates a point on a 1D cubic Bezier curve.
@@ -62,47 +74,58 @@ fn cubic_bezier(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
            (t_sq * t * p3);
 }
 
-@fragment
-fn fs_main(in: FragmentInput) -> @location(0) vec4<f32> {
-    let sampled_color = textureSample(t_diffuse, s_sampler, in.tex_coords);
+// Block light is slightly warm, so torch-lit caves read differently from daylight.
+const BLOCK_LIGHT_COLOR: vec3<f32> = vec3<f32>(1.0, 0.9, 0.75);
 
-    // Alpha Testing: If the pixel's alpha is very low, discard it completely.
-    if (sampled_color.a < 0.1) {
-        discard;
-    }
+// Size of one block texture in the atlas (ATLAS_COLS × ATLAS_ROWS in mesher.rs).
+const ATLAS_TILE_SIZE: vec2<f32> = vec2<f32>(1.0 / 16.0, 1.0);
 
-    let normalized_light = f32(in.sky_light) / 15.0;
+// Brightness of a fully occluded corner relative to an open one.
+const AO_MIN_BRIGHTNESS: f32 = 0.45;
+
+// Maps a 0..15 light level to a brightness in [0.05, 1.0].
+fn light_intensity(level: f32) -> f32 {
+    let normalized_light = level / 15.0;
 
     // 1. Calculate the curve's output (from 0.0 to 1.0) using control points
     //    that create a sharp "ease-in" effect.
     let bezier_y = cubic_bezier(0.0, 0.0, 0.1, 1.0, normalized_light);
 
     // 2. Remap the curve's output to your desired brightness range [0.05, 1.0].
-    let light_intensity = 0.05 + bezier_y * 0.95;
-
-    var final_color = sampled_color.rgb * light_intensity;
-
-    // Sentinel color for Grass Top, set in main.rs: [0.1, 0.9, 0.1]
-    let grass_top_sentinel = vec3<f32>(0.1, 0.9, 0.1);
-    // Sentinel color for Oak Leaves
-    let oak_leaves_sentinel = vec3<f32>(0.1, 0.9, 0.2);
-
-    // Compare floating point colors with a small epsilon for precision issues
-    let grass_color_diff = abs(in.original_color - grass_top_sentinel);
-    let is_grass_top = grass_color_diff.x < 0.01 && grass_color_diff.y < 0.01 && grass_color_diff.z < 0.01;
-
-    let leaves_color_diff = abs(in.original_color - oak_leaves_sentinel);
-    let is_potential_oak_leaves = leaves_color_diff.x < 0.01 && leaves_color_diff.y < 0.01 && leaves_color_diff.z < 0.01;
-
-    if (is_grass_top) {
-        let intensity = sampled_color.r;
-        let tinted_color = vec3<f32>(intensity * 0.4, intensity * 0.9, intensity * 0.35);
-        final_color = tinted_color * light_intensity;
-    } else if (is_potential_oak_leaves) {
-        let intensity = sampled_color.r;
-        let oak_tinted_color = vec3<f32>(intensity * 0.25, intensity * 0.55, intensity * 0.15);
-        final_color = oak_tinted_color * light_intensity;
+    return 0.05 + bezier_y * 0.95;
+}
+
+// Textured, lit colour of a fragment, keeping the texture's alpha.
+fn shade(in: FragmentInput) -> vec4<f32> {
+    // Merged quads span several blocks; repeat the block's texture once per block.
+    let atlas_uv = in.atlas_tile + fract(in.tex_coords) * ATLAS_TILE_SIZE;
+    let sampled_color = textureSample(t_diffuse, s_sampler, atlas_uv);
+
+    // Alpha Testing: If the pixel's alpha is very low, discard it completely.
+    if (sampled_color.a < 0.1) {
+        discard;
     }
 
-    return vec4<f32>(final_color.rgb, 1.0); // Return full alpha
+    // Whichever channel is brighter wins, per colour component.
+    let sky_light = vec3<f32>(light_intensity(in.sky_light));
+    let block_light = light_intensity(in.block_light) * BLOCK_LIGHT_COLOR;
+    let ao = mix(AO_MIN_BRIGHTNESS, 1.0, in.ao / 3.0);
+    let light = max(sky_light, block_light) * ao;
+
+    // Tinted textures (grass top, leaves) are greyscale; the tint gives them their biome
+    // colour. Untinted faces carry white and keep their texture colour.
+    let final_color = sampled_color.rgb * in.tint * light;
+
+    return vec4<f32>(final_color.rgb, sampled_color.a);
+}
+
+@fragment
+fn fs_main(in: FragmentInput) -> @location(0) vec4<f32> {
+    return vec4<f32>(shade(in).rgb, 1.0); // Return full alpha
+}
+
+// Fluid surfaces are blended over what is behind them using the texture's alpha.
+@fragment
+fn fs_fluid(in: FragmentInput) -> @location(0) vec4<f32> {
+    return shade(in);
 }
@@ -0,0 +1,86 @@
+// Fixed simulation timestep. Each frame hands over the real time that passed; the clock turns
+// it into whole ticks of TICK_DURATION and keeps the remainder for the next frame, so physics
+// and world ticks run at the same speed whatever the frame rate.
+
+use std::time::Duration;
+
+pub const TICKS_PER_SECOND: u32 = 60;
+pub const TICK_DURATION: Duration = Duration::from_nanos(1_000_000_000 / TICKS_PER_SECOND as u64);
+
+// After a long stall (a breakpoint, a dragged window) the simulation skips the lost time
+// instead of running hundreds of ticks in one frame and stalling again.
+const MAX_TICKS_PER_FRAME: u32 = 10;
+
+#[derive(Debug, Default)]
+pub struct FixedTimestep {
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds a frame's worth of real time and returns how many ticks to run for it.
+    pub fn advance(&mut self, frame_time: Duration) -> u32 {
+        self.accumulator += frame_time;
+        let mut ticks = 0;
+        while self.accumulator >= TICK_DURATION {
+            self.accumulator -= TICK_DURATION;
+            ticks += 1;
+        }
+        ticks.min(MAX_TICKS_PER_FRAME)
+    }
+
+    // How far (0 to 1) the time left over has got towards the next tick; used to interpolate
+    // between the last two simulated states when rendering.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / TICK_DURATION.as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticks_in_one_second(frames_per_second: u32) -> u32 {
+        let mut timestep = FixedTimestep::new();
+        let frame_time = Duration::from_secs(1) / frames_per_second;
+        (0..frames_per_second)
+            .map(|_| timestep.advance(frame_time))
+            .sum()
+    }
+
+    #[test]
+    fn test_tick_rate_does_not_depend_on_frame_rate() {
+        for frames_per_second in [30, 60, 75, 144, 240] {
+            let ticks = ticks_in_one_second(frames_per_second);
+            assert!(
+                ticks.abs_diff(TICKS_PER_SECOND) <= 1,
+                "{} ticks at {} FPS",
+                ticks,
+                frames_per_second
+            );
+        }
+    }
+
+    #[test]
+    fn test_alpha_is_the_fraction_of_a_tick_left_over() {
+        let mut timestep = FixedTimestep::new();
+        assert_eq!(timestep.advance(TICK_DURATION / 4), 0);
+        assert!((timestep.alpha() - 0.25).abs() < 1e-3);
+        assert_eq!(timestep.advance(TICK_DURATION), 1);
+        assert!((timestep.alpha() - 0.25).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_long_stall_is_capped() {
+        let mut timestep = FixedTimestep::new();
+        assert_eq!(
+            timestep.advance(Duration::from_secs(5)),
+            MAX_TICKS_PER_FRAME
+        );
+        assert!(timestep.alpha() < 1.0);
+        assert_eq!(timestep.advance(Duration::ZERO), 0);
+    }
+}
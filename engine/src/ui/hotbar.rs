@@ -4,7 +4,12 @@ use super::item::{ItemType, ItemStack};
 use crate::block::BlockType;
 use wgpu::util::DeviceExt;
 
-const NUM_SLOTS: usize = 9;
+pub const NUM_SLOTS: usize = 9;
+// The vertex buffer holds the background quad, one quad per slot and then one selection
+// frame per slot, drawn behind the selected slot.
+const QUAD_VERTICES: u32 = 6;
+const SLOTS_START: u32 = QUAD_VERTICES;
+const FRAMES_START: u32 = SLOTS_START + NUM_SLOTS as u32 * QUAD_VERTICES;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -36,12 +41,13 @@ impl HotbarVertex {
 
 pub struct Hotbar {
     vertex_buffer: wgpu::Buffer,
-    num_vertices: u32,
     render_pipeline: wgpu::RenderPipeline,
     projection_bind_group: wgpu::BindGroup,
     pub items: [Option<ItemStack>; NUM_SLOTS],
     // Store positions to avoid recalculating them in draw loop
     pub slot_positions: [[f32; 2]; NUM_SLOTS],
+    // Slot whose block is placed with the right mouse button.
+    selected_slot: usize,
 }
 
 impl Hotbar {
@@ -71,7 +77,10 @@ impl Hotbar {
         let start_x = (config.width as f32 - grid_width) / 2.0;
         let start_y_slots = config.height as f32 - SLOT_SIZE - (SLOT_MARGIN * 2.0);
         let slot_color = [0.3, 0.3, 0.3, 0.8];
+        let frame_color = [0.9, 0.9, 0.9, 0.9];
+        let frame_size = SLOT_SIZE + SLOT_MARGIN;
         let mut slot_positions = [[0.0; 2]; NUM_SLOTS];
+        let mut frame_vertices: Vec<HotbarVertex> = Vec::new();
 
         for col in 0..NUM_SLOTS {
             let x = start_x + col as f32 * TOTAL_SLOT_SIZE;
@@ -85,14 +94,23 @@ impl Hotbar {
                 HotbarVertex { position: [x + SLOT_SIZE, y], color: slot_color },
                 HotbarVertex { position: [x + SLOT_SIZE, y + SLOT_SIZE], color: slot_color },
             ]);
+            let (fx, fy) = (x - SLOT_MARGIN / 2.0, y - SLOT_MARGIN / 2.0);
+            frame_vertices.extend_from_slice(&[
+                HotbarVertex { position: [fx, fy], color: frame_color },
+                HotbarVertex { position: [fx + frame_size, fy + frame_size], color: frame_color },
+                HotbarVertex { position: [fx, fy + frame_size], color: frame_color },
+                HotbarVertex { position: [fx, fy], color: frame_color },
+                HotbarVertex { position: [fx + frame_size, fy], color: frame_color },
+                HotbarVertex { position: [fx + frame_size, fy + frame_size], color: frame_color },
+            ]);
         }
+        vertices.extend(frame_vertices);
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Hotbar Vertex Buffer"),
             contents: bytemuck::cast_slice(&vertices),
             usage: wgpu::BufferUsages::VERTEX,
         });
-        let num_vertices = vertices.len() as u32;
 
         let projection_matrix = glam::Mat4::orthographic_rh(
             0.0,
@@ -172,23 +190,40 @@ impl Hotbar {
         });
 
         let mut items: [Option<ItemStack>; NUM_SLOTS] = Default::default();
-        items[0] = Some(ItemStack::new(ItemType::Block(BlockType::Dirt), 1));
+        items[0] = Some(ItemStack::new(ItemType::Block(BlockType::DIRT), 1));
 
         Self {
             vertex_buffer,
-            num_vertices,
             render_pipeline,
             projection_bind_group,
             items,
             slot_positions,
+            selected_slot: 0,
+        }
+    }
+
+    // Out of range slots are ignored.
+    pub fn select_slot(&mut self, slot: usize) {
+        if slot < NUM_SLOTS {
+            self.selected_slot = slot;
         }
     }
 
+    // The block in the selected slot, if it holds one.
+    pub fn selected_block(&self) -> Option<BlockType> {
+        self.items[self.selected_slot].map(|stack| match stack.item_type {
+            ItemType::Block(block_type) => block_type,
+        })
+    }
+
     pub fn draw<'pass>(&'pass self, render_pass: &mut wgpu::RenderPass<'pass>) {
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_bind_group(0, &self.projection_bind_group, &[]);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        render_pass.draw(0..self.num_vertices, 0..1);
+        render_pass.draw(0..SLOTS_START, 0..1);
+        let frame_start = FRAMES_START + self.selected_slot as u32 * QUAD_VERTICES;
+        render_pass.draw(frame_start..frame_start + QUAD_VERTICES, 0..1);
+        render_pass.draw(SLOTS_START..FRAMES_START, 0..1);
     }
 
     pub fn handle_mouse_click(
@@ -0,0 +1,263 @@
+// Cave culling. For every 16³ section we record which of its six faces can see each other
+// through non-opaque blocks; a breadth-first search from the camera's section then only
+// walks through faces that connect, so sections sealed off behind rock are never drawn.
+
+use crate::chunk::{CHUNK_DEPTH, CHUNK_WIDTH, Chunk, MIN_Y, SECTION_COUNT, SECTION_SIZE};
+use crate::cube_geometry::CubeFace;
+use crate::physics::AABB;
+use glam::{IVec3, Vec3};
+use std::collections::{HashSet, VecDeque};
+
+// One bit per (entry face, exit face) pair, indexed by `CubeFace::index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionVisibility(u64);
+
+impl SectionVisibility {
+    pub const NONE: Self = SectionVisibility(0);
+    pub const ALL: Self = SectionVisibility((1 << 36) - 1);
+
+    pub fn can_see_through(self, from: CubeFace, to: CubeFace) -> bool {
+        self.0 & Self::bit(from.index(), to.index()) != 0
+    }
+
+    fn bit(from: usize, to: usize) -> u64 {
+        1 << (from * 6 + to)
+    }
+
+    // Connects every pair of faces in `faces` (a bitmask over `CubeFace::index`).
+    fn connect_all(&mut self, faces: u8) {
+        for from in (0..6).filter(|&from| faces & (1 << from) != 0) {
+            for to in (0..6).filter(|&to| faces & (1 << to) != 0) {
+                self.0 |= Self::bit(from, to);
+            }
+        }
+    }
+}
+
+// The section holding `position`, clamped to the world's height so a camera above the build
+// limit or below the bottom still starts the search from a real section.
+pub fn section_containing(position: Vec3) -> IVec3 {
+    let section_y = ((position.y.floor() as i32 - MIN_Y) / SECTION_SIZE as i32)
+        .clamp(0, SECTION_COUNT as i32 - 1);
+    IVec3::new(
+        (position.x / CHUNK_WIDTH as f32).floor() as i32,
+        section_y,
+        (position.z / CHUNK_DEPTH as f32).floor() as i32,
+    )
+}
+
+pub fn section_bounds(section: IVec3) -> AABB {
+    let min = Vec3::new(
+        (section.x * CHUNK_WIDTH as i32) as f32,
+        (MIN_Y + section.y * SECTION_SIZE as i32) as f32,
+        (section.z * CHUNK_DEPTH as i32) as f32,
+    );
+    AABB::new(
+        min,
+        min + Vec3::new(CHUNK_WIDTH as f32, SECTION_SIZE as f32, CHUNK_DEPTH as f32),
+    )
+}
+
+// Face connectivity of every section of a chunk, bottom to top.
+pub fn chunk_visibility(chunk: &Chunk) -> Vec<SectionVisibility> {
+    (0..SECTION_COUNT)
+        .map(|section| section_visibility(chunk, section))
+        .collect()
+}
+
+// Flood-fills each pocket of non-opaque blocks in the section and connects all the faces
+// that pocket touches.
+pub fn section_visibility(chunk: &Chunk, section: usize) -> SectionVisibility {
+    if chunk.is_section_empty(section) {
+        return SectionVisibility::ALL;
+    }
+    const CELLS: usize = SECTION_SIZE * SECTION_SIZE * SECTION_SIZE;
+    let base_y = section * SECTION_SIZE;
+    let cell_index = |pos: IVec3| {
+        ((pos.y as usize * SECTION_SIZE) + pos.z as usize) * SECTION_SIZE + pos.x as usize
+    };
+    // Cells not yet reached by any fill; opaque cells are never open.
+    let mut open = vec![false; CELLS];
+    for x in 0..CHUNK_WIDTH {
+        for y in 0..SECTION_SIZE {
+            for z in 0..CHUNK_DEPTH {
+                open[cell_index(IVec3::new(x as i32, y as i32, z as i32))] = chunk
+                    .get_block(x, base_y + y, z)
+                    .is_some_and(|block| block.is_transparent());
+            }
+        }
+    }
+    let mut visibility = SectionVisibility::NONE;
+    let mut queue = Vec::new();
+    for start in 0..CELLS {
+        if !open[start] {
+            continue;
+        }
+        open[start] = false;
+        let start_pos = IVec3::new(
+            (start % SECTION_SIZE) as i32,
+            (start / (SECTION_SIZE * SECTION_SIZE)) as i32,
+            ((start / SECTION_SIZE) % SECTION_SIZE) as i32,
+        );
+        queue.push(start_pos);
+        let mut touched_faces = 0u8;
+        while let Some(pos) = queue.pop() {
+            for face in CubeFace::all_faces() {
+                let next = pos + face.normal();
+                if next.cmplt(IVec3::ZERO).any()
+                    || next.cmpge(IVec3::splat(SECTION_SIZE as i32)).any()
+                {
+                    touched_faces |= 1 << face.index();
+                } else if open[cell_index(next)] {
+                    open[cell_index(next)] = false;
+                    queue.push(next);
+                }
+            }
+        }
+        visibility.connect_all(touched_faces);
+    }
+    visibility
+}
+
+// Sections (chunk x, section index, chunk z) that may be visible from `start`.
+// `visibility_at` gives a section's connectivity, or None if there is nothing there to walk
+// through (unloaded, or outside the world's height); `in_view` rejects sections outside the
+// view frustum. The start section itself is always visible.
+pub fn visible_sections(
+    start: IVec3,
+    visibility_at: impl Fn(IVec3) -> Option<SectionVisibility>,
+    in_view: impl Fn(IVec3) -> bool,
+) -> HashSet<IVec3> {
+    let mut visible = HashSet::from([start]);
+    // Section, the face the search entered it through, and the directions taken so far.
+    let mut queue = VecDeque::from([(start, None::<CubeFace>, 0u8)]);
+    while let Some((section, entered_through, travelled)) = queue.pop_front() {
+        let Some(visibility) = visibility_at(section) else {
+            continue;
+        };
+        for face in CubeFace::all_faces() {
+            // Never turn back against a direction already taken: a line of sight from the
+            // camera only moves away from it along each axis.
+            if travelled & (1 << face.opposite().index()) != 0 {
+                continue;
+            }
+            if entered_through.is_some_and(|entry| !visibility.can_see_through(entry, face)) {
+                continue;
+            }
+            let next = section + face.normal();
+            if visible.contains(&next) || visibility_at(next).is_none() || !in_view(next) {
+                continue;
+            }
+            visible.insert(next);
+            queue.push_back((next, Some(face.opposite()), travelled | (1 << face.index())));
+        }
+    }
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockType;
+    use std::collections::HashMap;
+
+    const SECTION: usize = 4;
+
+    fn fill(chunk: &mut Chunk, min: (usize, usize, usize), max: (usize, usize, usize)) {
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                for z in min.2..=max.2 {
+                    chunk
+                        .set_block(x, SECTION * SECTION_SIZE + y, z, BlockType::STONE)
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_section_sees_through_every_face() {
+        let chunk = Chunk::new(0, 0);
+        assert_eq!(section_visibility(&chunk, SECTION), SectionVisibility::ALL);
+    }
+
+    #[test]
+    fn test_solid_section_sees_through_nothing() {
+        let mut chunk = Chunk::new(0, 0);
+        fill(&mut chunk, (0, 0, 0), (15, 15, 15));
+        assert_eq!(section_visibility(&chunk, SECTION), SectionVisibility::NONE);
+    }
+
+    #[test]
+    fn test_floor_splits_top_from_bottom() {
+        let mut chunk = Chunk::new(0, 0);
+        fill(&mut chunk, (0, 8, 0), (15, 8, 15));
+        let visibility = section_visibility(&chunk, SECTION);
+        assert!(!visibility.can_see_through(CubeFace::Top, CubeFace::Bottom));
+        assert!(visibility.can_see_through(CubeFace::Top, CubeFace::Right));
+        assert!(visibility.can_see_through(CubeFace::Bottom, CubeFace::Front));
+        assert!(visibility.can_see_through(CubeFace::Left, CubeFace::Right));
+    }
+
+    #[test]
+    fn test_tunnel_connects_only_its_ends() {
+        let mut chunk = Chunk::new(0, 0);
+        fill(&mut chunk, (0, 0, 0), (15, 15, 15));
+        // Carve a tunnel along x through the middle.
+        for x in 0..CHUNK_WIDTH {
+            chunk
+                .set_block(x, SECTION * SECTION_SIZE + 7, 7, BlockType::AIR)
+                .unwrap();
+        }
+        let visibility = section_visibility(&chunk, SECTION);
+        assert!(visibility.can_see_through(CubeFace::Left, CubeFace::Right));
+        assert!(visibility.can_see_through(CubeFace::Right, CubeFace::Left));
+        assert!(!visibility.can_see_through(CubeFace::Left, CubeFace::Top));
+        assert!(!visibility.can_see_through(CubeFace::Front, CubeFace::Back));
+    }
+
+    // A row of sections along +X: open, then `middle`, then open again.
+    fn row_with_middle(middle: SectionVisibility) -> HashSet<IVec3> {
+        let sections = HashMap::from([
+            (IVec3::new(0, 0, 0), SectionVisibility::ALL),
+            (IVec3::new(1, 0, 0), middle),
+            (IVec3::new(2, 0, 0), SectionVisibility::ALL),
+        ]);
+        visible_sections(
+            IVec3::ZERO,
+            |section| sections.get(&section).copied(),
+            |_| true,
+        )
+    }
+
+    #[test]
+    fn test_search_stops_at_sealed_sections() {
+        let visible = row_with_middle(SectionVisibility::NONE);
+        // The wall itself can be seen, but nothing behind it.
+        assert!(visible.contains(&IVec3::new(1, 0, 0)));
+        assert!(!visible.contains(&IVec3::new(2, 0, 0)));
+    }
+
+    #[test]
+    fn test_search_follows_connected_faces() {
+        let mut tunnel = SectionVisibility::NONE;
+        tunnel.connect_all((1 << CubeFace::Left.index()) | (1 << CubeFace::Right.index()));
+        let visible = row_with_middle(tunnel);
+        assert!(visible.contains(&IVec3::new(2, 0, 0)));
+    }
+
+    #[test]
+    fn test_search_skips_sections_out_of_view() {
+        let sections = HashMap::from([
+            (IVec3::new(0, 0, 0), SectionVisibility::ALL),
+            (IVec3::new(1, 0, 0), SectionVisibility::ALL),
+            (IVec3::new(-1, 0, 0), SectionVisibility::ALL),
+        ]);
+        let visible = visible_sections(
+            IVec3::ZERO,
+            |section| sections.get(&section).copied(),
+            |section| section.x >= 0,
+        );
+        assert_eq!(visible, HashSet::from([IVec3::ZERO, IVec3::new(1, 0, 0)]));
+    }
+}
@@ -1,85 +1,503 @@
-use crate::block::{Block, BlockType};
-use crate::chunk::{CHUNK_DEPTH, CHUNK_HEIGHT, CHUNK_WIDTH, Chunk};
-use std::collections::{HashMap, VecDeque};
+use crate::biome::Biome;
+use crate::block::{Block, BlockType, MAX_FLUID_LEVEL};
+use crate::chunk::{
+    CHUNK_DEPTH, CHUNK_HEIGHT, CHUNK_WIDTH, Chunk, ChunkMemoryUsage, MAX_Y, MIN_Y, SECTION_COUNT,
+    SECTION_SIZE, local_y_to_world, world_y_to_local,
+};
+use crate::save::WorldSave;
+use crate::worldgen::WorldGenerator;
+use crate::worldgen::structures::BlockPlacement;
+use crate::worldgen::terrain::NoiseWorldGenerator;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+// A chunk column plus block coordinates local to that chunk.
+type ChunkLocalCoords = ((i32, i32), (usize, usize, usize));
+
+// Structure blocks one source chunk aimed at a target chunk, keyed by the source chunk.
+pub type PlacementsBySource = HashMap<(i32, i32), Vec<BlockPlacement>>;
+
+// Structure blocks waiting for their target chunk, by target and then by source chunk.
+pub type PendingPlacements = HashMap<(i32, i32), PlacementsBySource>;
+
+// Sky light comes from above and keeps its full strength going straight down; block light
+// comes from emissive blocks and fades by one level per block in every direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LightChannel {
+    Sky,
+    Block,
+}
+
+impl LightChannel {
+    fn level(self, block: &Block) -> u8 {
+        match self {
+            LightChannel::Sky => block.sky_light,
+            LightChannel::Block => block.block_light,
+        }
+    }
+}
 
 pub struct World {
-    chunks: HashMap<(i32, i32), Chunk>,
+    seed: u64,
+    generator: Arc<dyn WorldGenerator>,
+    // Shared so mesh workers can hold cheap snapshots; writes go through `Arc::make_mut`,
+    // which copies a chunk only while a snapshot of it is still alive.
+    chunks: HashMap<(i32, i32), Arc<Chunk>>,
+    // Structure blocks that generated chunks aimed at other chunks. They are applied
+    // whenever the target is loaded, and kept afterwards so a chunk that is unloaded and
+    // generated again gets its neighbours' trees back. Once the target has been saved they
+    // are part of its saved copy and are dropped.
+    pending_placements: PendingPlacements,
+    // Whether `pending_placements` changed since the placements file was last written.
+    placements_dirty: bool,
+    // Already loaded chunks changed by a neighbour's structures or by flowing fluid; they
+    // need a new mesh.
+    dirty_chunks: HashSet<(i32, i32)>,
+    // Blocks whose fluid state may change on the next `tick_fluids`.
+    scheduled_fluid_updates: HashSet<glam::IVec3>,
+    // Where chunks are loaded from and saved to; None keeps the world in memory only.
+    save: Option<Arc<WorldSave>>,
 }
 
 impl World {
-    pub fn new() -> Self {
+    pub fn new(seed: u64) -> Self {
+        Self::with_generator(seed, Arc::new(NoiseWorldGenerator::default()))
+    }
+
+    pub fn with_generator(seed: u64, generator: Arc<dyn WorldGenerator>) -> Self {
         World {
+            seed,
+            generator,
             chunks: HashMap::new(),
+            pending_placements: HashMap::new(),
+            placements_dirty: false,
+            dirty_chunks: HashSet::new(),
+            scheduled_fluid_updates: HashSet::new(),
+            save: None,
         }
     }
 
+    // Loads chunks from `save` in preference to generating them and writes them back when
+    // they are unloaded. Structure blocks still waiting for their chunk are restored too.
+    pub fn with_save(mut self, save: Arc<WorldSave>) -> Self {
+        match save.load_pending_placements() {
+            Ok(pending) => self.pending_placements = pending,
+            Err(e) => eprintln!("Failed to load pending structure blocks: {}", e),
+        }
+        self.save = Some(save);
+        self
+    }
+
+    pub fn save(&self) -> Option<Arc<WorldSave>> {
+        self.save.clone()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn generator(&self) -> Arc<dyn WorldGenerator> {
+        Arc::clone(&self.generator)
+    }
+
     pub fn get_or_create_chunk(&mut self, chunk_x: i32, chunk_z: i32) -> &mut Chunk {
-        self.chunks.entry((chunk_x, chunk_z)).or_insert_with(|| {
-            let mut new_chunk = Chunk::new(chunk_x, chunk_z);
-            new_chunk.generate_terrain();
+        if !self.chunks.contains_key(&(chunk_x, chunk_z)) {
+            self.generate_chunk((chunk_x, chunk_z));
+        }
+        Arc::make_mut(
+            self.chunks
+                .get_mut(&(chunk_x, chunk_z))
+                .expect("Chunk was just generated"),
+        )
+    }
+
+    fn generate_chunk(&mut self, coord: (i32, i32)) {
+        let (chunk, placements) = load_or_generate_chunk_data(
+            self.generator.as_ref(),
+            self.seed,
+            self.save.as_deref(),
+            coord,
+        );
+        self.insert_chunk(chunk, placements);
+    }
+
+    // Adds a chunk produced by `load_or_generate_chunk_data` (possibly on another thread),
+    // together with the structure blocks it aims at other chunks, and applies structure
+    // blocks between it and its neighbours. Returns false if the chunk was already loaded,
+    // in which case the result is dropped.
+    pub fn insert_chunk(&mut self, mut new_chunk: Chunk, placements: Vec<BlockPlacement>) -> bool {
+        let coord = new_chunk.coord;
+        if self.chunks.contains_key(&coord) {
+            return false;
+        }
+
+        let mut changed = false;
+        if let Some(pending_by_source) = self.pending_placements.get(&coord) {
+            let mut sources: Vec<_> = pending_by_source.keys().copied().collect();
+            sources.sort_unstable();
+            for source in sources {
+                for placement in &pending_by_source[&source] {
+                    changed |= placement.apply_to(&mut new_chunk);
+                }
+            }
+        }
+
+        let mut outgoing: HashMap<(i32, i32), Vec<BlockPlacement>> = HashMap::new();
+        for placement in placements {
+            outgoing
+                .entry(placement.chunk_coord())
+                .or_default()
+                .push(placement);
+        }
+
+        let mut touched_neighbours = HashSet::new();
+        for (target, target_placements) in outgoing {
+            if let Some(neighbour) = self.chunks.get_mut(&target) {
+                let neighbour = Arc::make_mut(neighbour);
+                for placement in &target_placements {
+                    if placement.apply_to(neighbour) {
+                        touched_neighbours.insert(target);
+                    }
+                }
+            }
+            // Regenerating this chunk replaces what it queued before instead of adding to it.
+            self.pending_placements
+                .entry(target)
+                .or_default()
+                .insert(coord, target_placements);
+            self.placements_dirty = true;
+        }
+
+        // `generate_chunk_data` lit the chunk before its neighbours' structures arrived.
+        if changed {
             new_chunk.calculate_sky_light();
-            new_chunk
-        })
+        }
+        self.chunks.insert(coord, Arc::new(new_chunk));
+
+        for neighbour_coord in &touched_neighbours {
+            if let Some(neighbour) = self.chunks.get_mut(neighbour_coord) {
+                Arc::make_mut(neighbour).calculate_sky_light();
+            }
+        }
+        let mut relit = self.spread_light_across_borders(coord);
+        for &neighbour_coord in &touched_neighbours {
+            relit.extend(self.spread_light_across_borders(neighbour_coord));
+        }
+        // The new chunk has no mesh yet, so only its neighbours need a new one.
+        relit.remove(&coord);
+        self.dirty_chunks.extend(relit);
+        self.dirty_chunks.extend(touched_neighbours);
+        self.schedule_fluid_updates_for_new_chunk(coord);
+        true
+    }
+
+    // Fluid schedules are not saved, and updates for unloaded blocks are dropped. Instead,
+    // a chunk that loads schedules every fluid block in it (so water saved mid-flow or
+    // mid-drain carries on) and every fluid block next to it in its neighbours (so water
+    // that reached the edge of the loaded area flows on into it).
+    fn schedule_fluid_updates_for_new_chunk(&mut self, coord: (i32, i32)) {
+        let mut fluids = self.fluid_positions_in(coord);
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let neighbour_fluids = self.fluid_positions_in((coord.0 + dx, coord.1 + dz));
+            // Fluid on the side of the neighbour that faces the new chunk.
+            fluids.extend(neighbour_fluids.into_iter().filter(|pos| {
+                let (across, _) =
+                    World::world_to_column_coords((pos.x - dx) as f32, (pos.z - dz) as f32);
+                across == coord
+            }));
+        }
+        for pos in fluids {
+            self.schedule_fluid_updates_around(pos);
+        }
+    }
+
+    fn fluid_positions_in(&self, coord: (i32, i32)) -> Vec<glam::IVec3> {
+        let Some(chunk) = self.chunks.get(&coord) else {
+            return Vec::new();
+        };
+        chunk
+            .fluid_positions()
+            .into_iter()
+            .map(|(x, y, z)| {
+                glam::IVec3::new(
+                    coord.0 * CHUNK_WIDTH as i32 + x as i32,
+                    local_y_to_world(y),
+                    coord.1 * CHUNK_DEPTH as i32 + z as i32,
+                )
+            })
+            .collect()
+    }
+
+    // Lets light flow across the borders between a chunk and its loaded neighbours. Chunks
+    // are lit on their own, as if nothing lay beyond their edges, so light only ever has to
+    // be added here. Returns the chunks whose light changed.
+    fn spread_light_across_borders(&mut self, coord: (i32, i32)) -> HashSet<(i32, i32)> {
+        let mut relit = HashSet::new();
+        for channel in [LightChannel::Sky, LightChannel::Block] {
+            let mut queue = VecDeque::new();
+            for (pos, level) in self.border_light_seeds(coord, channel) {
+                // An earlier seed may already have lit this one brighter.
+                if level > self.get_light_level(pos, channel) {
+                    relit.extend(self.set_light_level(pos, channel, level));
+                    queue.push_back(pos);
+                }
+            }
+            relit.extend(self.run_light_propagation_queue(queue, channel));
+        }
+        relit
+    }
+
+    // Transparent blocks along a chunk's borders, on either side, that the block facing
+    // them across the border would make brighter, with the level they should get.
+    fn border_light_seeds(
+        &self,
+        coord: (i32, i32),
+        channel: LightChannel,
+    ) -> Vec<(glam::IVec3, u8)> {
+        let Some(chunk) = self.chunks.get(&coord) else {
+            return Vec::new();
+        };
+        let world_pos = |chunk_coord: (i32, i32), (x, y, z): (usize, usize, usize)| {
+            glam::IVec3::new(
+                chunk_coord.0 * CHUNK_WIDTH as i32 + x as i32,
+                local_y_to_world(y),
+                chunk_coord.1 * CHUNK_DEPTH as i32 + z as i32,
+            )
+        };
+
+        let mut seeds = Vec::new();
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let neighbour_coord = (coord.0 + dx, coord.1 + dz);
+            let Some(neighbour) = self.chunks.get(&neighbour_coord) else {
+                continue;
+            };
+            for i in 0..CHUNK_WIDTH {
+                // Columns facing each other across the border: (x, z) in `chunk`, then in
+                // `neighbour`.
+                let (inside, outside) = match (dx, dz) {
+                    (1, 0) => ((CHUNK_WIDTH - 1, i), (0, i)),
+                    (-1, 0) => ((0, i), (CHUNK_WIDTH - 1, i)),
+                    (0, 1) => ((i, CHUNK_DEPTH - 1), (i, 0)),
+                    _ => ((i, 0), (i, CHUNK_DEPTH - 1)),
+                };
+                for section_index in 0..SECTION_COUNT {
+                    // Open sky on both sides is equally bright.
+                    if chunk.is_section_empty(section_index)
+                        && neighbour.is_section_empty(section_index)
+                    {
+                        continue;
+                    }
+                    for y in section_index * SECTION_SIZE..(section_index + 1) * SECTION_SIZE {
+                        let (Some(a), Some(b)) = (
+                            chunk.get_block(inside.0, y, inside.1),
+                            neighbour.get_block(outside.0, y, outside.1),
+                        ) else {
+                            continue;
+                        };
+                        let (light_a, light_b) = (channel.level(&a), channel.level(&b));
+                        if light_a > light_b + 1 && b.is_transparent() {
+                            seeds.push((
+                                world_pos(neighbour_coord, (outside.0, y, outside.1)),
+                                light_a - 1,
+                            ));
+                        } else if light_b > light_a + 1 && a.is_transparent() {
+                            seeds.push((world_pos(coord, (inside.0, y, inside.1)), light_b - 1));
+                        }
+                    }
+                }
+            }
+        }
+        seeds
+    }
+
+    // Loaded chunks whose blocks changed behind the renderer's back since the last call.
+    pub fn take_dirty_chunks(&mut self) -> Vec<(i32, i32)> {
+        self.dirty_chunks.drain().collect()
+    }
+
+    // Y coordinate just above the highest solid block of a column, generating its chunk
+    // if needed. Used to place the player on the surface.
+    pub fn surface_height(&mut self, world_x: i32, world_z: i32) -> i32 {
+        let ((chunk_x, chunk_z), (local_x, local_z)) =
+            World::world_to_column_coords(world_x as f32, world_z as f32);
+        let chunk = self.get_or_create_chunk(chunk_x, chunk_z);
+        (0..CHUNK_HEIGHT)
+            .rev()
+            .find(|&y| {
+                chunk
+                    .get_block(local_x, y, local_z)
+                    .is_some_and(|block| block.is_solid())
+            })
+            .map_or(MIN_Y, |y| local_y_to_world(y) + 1)
+    }
+
+    pub fn loaded_chunk_coords(&self) -> Vec<(i32, i32)> {
+        self.chunks.keys().copied().collect()
+    }
+
+    pub fn loaded_chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn chunk_memory_usage(&self) -> ChunkMemoryUsage {
+        let mut usage = ChunkMemoryUsage::default();
+        for chunk in self.chunks.values() {
+            usage += chunk.memory_usage();
+        }
+        usage
+    }
+
+    // Drops a chunk from memory, saving it first if the world has a save. Without one,
+    // placements still queued for it stay queued, so structures from neighbours are applied
+    // again if it is generated anew.
+    pub fn unload_chunk(&mut self, chunk_x: i32, chunk_z: i32) -> Option<Chunk> {
+        let coord = (chunk_x, chunk_z);
+        self.dirty_chunks.remove(&coord);
+        let chunk = self.chunks.remove(&coord).map(Arc::unwrap_or_clone)?;
+        if let Err(e) = self.save_chunk(&chunk) {
+            eprintln!("Failed to save chunk {:?}: {}", coord, e);
+        }
+        // Rewrite the placements file straight away if it is out of date, so it matches the
+        // chunks on disk after a crash: an old copy would apply structure blocks again on top
+        // of a saved chunk, or lose the ones a saved chunk (which loads without its
+        // generation's placements) queued for chunks that were never generated.
+        if self.placements_dirty
+            && let Err(e) = self.save_pending_placements()
+        {
+            eprintln!("Failed to save pending structure blocks: {}", e);
+        }
+        Some(chunk)
+    }
+
+    // Saves a chunk and drops the structure blocks queued for it, since the saved copy
+    // already contains them.
+    fn save_chunk(&mut self, chunk: &Chunk) -> std::io::Result<()> {
+        let Some(save) = &self.save else {
+            return Ok(());
+        };
+        save.save_chunk(chunk)?;
+        if self.pending_placements.remove(&chunk.coord).is_some() {
+            self.placements_dirty = true;
+        }
+        Ok(())
+    }
+
+    fn save_pending_placements(&mut self) -> std::io::Result<()> {
+        if let Some(save) = &self.save {
+            save.save_pending_placements(&self.pending_placements)?;
+            self.placements_dirty = false;
+        }
+        Ok(())
+    }
+
+    // Saves every loaded chunk and the structure blocks still waiting for unloaded ones.
+    // Does nothing without a save.
+    pub fn save_all(&mut self) -> std::io::Result<()> {
+        if self.save.is_none() {
+            return Ok(());
+        }
+        let chunks: Vec<Arc<Chunk>> = self.chunks.values().cloned().collect();
+        for chunk in chunks {
+            self.save_chunk(&chunk)?;
+        }
+        self.save_pending_placements()
     }
 
     pub fn get_chunk(&self, chunk_x: i32, chunk_z: i32) -> Option<&Chunk> {
-        self.chunks.get(&(chunk_x, chunk_z))
+        self.chunks.get(&(chunk_x, chunk_z)).map(|chunk| &**chunk)
     }
 
+    // A read-only handle to a chunk that stays valid while the world keeps changing.
+    pub fn chunk_snapshot(&self, chunk_x: i32, chunk_z: i32) -> Option<Arc<Chunk>> {
+        self.chunks.get(&(chunk_x, chunk_z)).cloned()
+    }
+
+    // Chunk column and chunk-local block coordinates of a world position. Local y counts
+    // from MIN_Y (`Chunk::section_index` turns it into a section). None outside the world's
+    // vertical range.
     pub fn world_to_chunk_coords(
         world_x: f32,
         world_y: f32,
         world_z: f32,
-    ) -> ((i32, i32), (usize, usize, usize)) {
+    ) -> Option<ChunkLocalCoords> {
+        let local_y = world_y_to_local(world_y.floor() as i32)?;
+        let (chunk_coord, (local_x, local_z)) = World::world_to_column_coords(world_x, world_z);
+        Some((chunk_coord, (local_x, local_y, local_z)))
+    }
+
+    pub fn world_to_column_coords(world_x: f32, world_z: f32) -> ((i32, i32), (usize, usize)) {
         let chunk_x = (world_x / CHUNK_WIDTH as f32).floor() as i32;
         let chunk_z = (world_z / CHUNK_DEPTH as f32).floor() as i32;
         let local_x = ((world_x % CHUNK_WIDTH as f32) + CHUNK_WIDTH as f32) % CHUNK_WIDTH as f32;
         let local_z = ((world_z % CHUNK_DEPTH as f32) + CHUNK_DEPTH as f32) % CHUNK_DEPTH as f32;
-        let clamped_y = world_y.max(0.0).min(CHUNK_HEIGHT as f32 - 1.0);
-        (
-            (chunk_x, chunk_z),
-            (local_x as usize, clamped_y as usize, local_z as usize),
-        )
+        ((chunk_x, chunk_z), (local_x as usize, local_z as usize))
+    }
+
+    // Biome of the column containing the given position, if its chunk is loaded.
+    pub fn biome_at_world(&self, world_x: f32, world_z: f32) -> Option<Biome> {
+        let ((chunk_x, chunk_z), (local_x, local_z)) =
+            World::world_to_column_coords(world_x, world_z);
+        self.get_chunk(chunk_x, chunk_z)
+            .map(|chunk| chunk.get_biome(local_x, local_z))
     }
 
-    pub fn get_block_at_world(&self, world_x: f32, world_y: f32, world_z: f32) -> Option<&Block> {
+    pub fn get_block_at_world(&self, world_x: f32, world_y: f32, world_z: f32) -> Option<Block> {
         let ((chunk_x, chunk_z), (local_x, local_y, local_z)) =
-            World::world_to_chunk_coords(world_x, world_y, world_z);
-        if local_y >= CHUNK_HEIGHT {
-            return None;
-        }
+            World::world_to_chunk_coords(world_x, world_y, world_z)?;
         self.get_chunk(chunk_x, chunk_z)
             .and_then(|chunk| chunk.get_block(local_x, local_y, local_z))
     }
 
-    fn get_light_level(&self, pos: glam::IVec3) -> u8 {
-        if pos.y < 0 || pos.y >= CHUNK_HEIGHT as i32 {
-            return 0;
-        }
+    fn block_at(&self, pos: glam::IVec3) -> Option<Block> {
         self.get_block_at_world(pos.x as f32, pos.y as f32, pos.z as f32)
-            .map_or(0, |b| b.sky_light)
     }
 
-    fn set_light_level(&mut self, pos: glam::IVec3, level: u8) {
-        if pos.y < 0 || pos.y >= CHUNK_HEIGHT as i32 {
-            return;
-        }
+    fn get_light_level(&self, pos: glam::IVec3, channel: LightChannel) -> u8 {
+        self.get_block_at_world(pos.x as f32, pos.y as f32, pos.z as f32)
+            .map_or(0, |b| channel.level(&b))
+    }
+
+    // Returns the chunk that was changed, if the position is loaded.
+    fn set_light_level(
+        &mut self,
+        pos: glam::IVec3,
+        channel: LightChannel,
+        level: u8,
+    ) -> Option<(i32, i32)> {
         let ((chunk_x, chunk_z), (lx, ly, lz)) =
-            World::world_to_chunk_coords(pos.x as f32, pos.y as f32, pos.z as f32);
-        if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_z)) {
-            chunk.set_block_light(lx, ly, lz, level);
+            World::world_to_chunk_coords(pos.x as f32, pos.y as f32, pos.z as f32)?;
+        let chunk = Arc::make_mut(self.chunks.get_mut(&(chunk_x, chunk_z))?);
+        match channel {
+            LightChannel::Sky => chunk.set_sky_light(lx, ly, lz, level),
+            LightChannel::Block => chunk.set_block_light(lx, ly, lz, level),
+        }
+        Some((chunk_x, chunk_z))
+    }
+
+    // Light a block gives off by itself in a channel. The sky channel has no emitters.
+    fn get_light_emission(&self, pos: glam::IVec3, channel: LightChannel) -> u8 {
+        match channel {
+            LightChannel::Sky => 0,
+            LightChannel::Block => self
+                .get_block_at_world(pos.x as f32, pos.y as f32, pos.z as f32)
+                .map_or(0, |b| b.light_emission()),
         }
     }
 
     fn is_block_transparent(&self, pos: glam::IVec3) -> bool {
-        if pos.y < 0 || pos.y >= CHUNK_HEIGHT as i32 {
+        if !(MIN_Y..MAX_Y).contains(&pos.y) {
             return true;
         }
         self.get_block_at_world(pos.x as f32, pos.y as f32, pos.z as f32)
             .map_or(true, |b| b.is_transparent())
     }
 
-    fn propagate_light_addition(&mut self, new_air_block_pos: glam::IVec3) {
+    // Returns the chunks whose light changed.
+    fn propagate_light_addition(
+        &mut self,
+        new_air_block_pos: glam::IVec3,
+        channel: LightChannel,
+    ) -> HashSet<(i32, i32)> {
         let mut max_light_from_neighbors: u8 = 0;
         let mut light_propagation_queue = VecDeque::new();
 
@@ -94,46 +512,57 @@ impl World {
         ] {
             let neighbor_pos = new_air_block_pos + offset;
 
-            // Light can only pass from a neighbor if the neighbor is transparent.
-            if !self.is_block_transparent(neighbor_pos) {
+            // Light can only pass from a neighbor if the neighbor is transparent or emits it.
+            if !self.is_block_transparent(neighbor_pos)
+                && self.get_light_emission(neighbor_pos, channel) == 0
+            {
                 continue;
             }
 
-            let neighbor_light = self.get_light_level(neighbor_pos);
+            let neighbor_light = self.get_light_level(neighbor_pos, channel);
             if neighbor_light == 0 {
                 continue;
             }
 
-            let potential_light = if offset == glam::IVec3::Y && neighbor_light == 15 {
-                15
-            } else {
-                neighbor_light - 1
-            };
+            let potential_light =
+                if channel == LightChannel::Sky && offset == glam::IVec3::Y && neighbor_light == 15
+                {
+                    15
+                } else {
+                    neighbor_light - 1
+                };
 
             if potential_light > max_light_from_neighbors {
                 max_light_from_neighbors = potential_light;
             }
         }
 
-        self.set_light_level(new_air_block_pos, max_light_from_neighbors);
+        let mut relit: HashSet<_> = self
+            .set_light_level(new_air_block_pos, channel, max_light_from_neighbors)
+            .into_iter()
+            .collect();
 
         if max_light_from_neighbors > 0 {
             light_propagation_queue.push_back(new_air_block_pos);
         }
 
-        self.run_light_propagation_queue(light_propagation_queue);
+        relit.extend(self.run_light_propagation_queue(light_propagation_queue, channel));
+        relit
     }
 
+    // Returns the chunks whose light changed.
     fn propagate_light_removal(
         &mut self,
         new_solid_block_pos: glam::IVec3,
         light_level_removed: u8,
-    ) {
+        channel: LightChannel,
+    ) -> HashSet<(i32, i32)> {
+        let mut relit = HashSet::new();
         if light_level_removed == 0 {
-            return;
+            return relit;
         }
 
-        self.set_light_level(new_solid_block_pos, 0);
+        relit.extend(self.set_light_level(new_solid_block_pos, channel, 0));
 
         let mut removal_queue = VecDeque::new();
         let mut relight_queue = VecDeque::new();
@@ -149,29 +578,38 @@ impl World {
                 glam::IVec3::NEG_Z,
             ] {
                 let neighbor_pos = pos + offset;
-                let neighbor_light = self.get_light_level(neighbor_pos);
+                let neighbor_light = self.get_light_level(neighbor_pos, channel);
 
                 if neighbor_light == 0 {
                     continue;
                 }
 
+                // Emitters keep their own light and fill the darkened area back in.
+                let emission = self.get_light_emission(neighbor_pos, channel);
+                if emission > 0 {
+                    relit.extend(self.set_light_level(neighbor_pos, channel, emission));
+                    relight_queue.push_back(neighbor_pos);
+                    continue;
+                }
+
                 if neighbor_light < light_level {
-                    self.set_light_level(neighbor_pos, 0);
+                    relit.extend(self.set_light_level(neighbor_pos, channel, 0));
                     removal_queue.push_back((neighbor_pos, neighbor_light));
                 } else {
-                    if self.should_be_relit(neighbor_pos) {
+                    if self.should_be_relit(neighbor_pos, channel) {
                         relight_queue.push_back(neighbor_pos);
                     } else {
-                        self.set_light_level(neighbor_pos, 0);
+                        relit.extend(self.set_light_level(neighbor_pos, channel, 0));
                         removal_queue.push_back((neighbor_pos, neighbor_light));
                     }
                 }
             }
         }
-        self.run_light_propagation_queue(relight_queue);
+        relit.extend(self.run_light_propagation_queue(relight_queue, channel));
+        relit
     }
 
-    fn should_be_relit(&self, pos: glam::IVec3) -> bool {
+    fn should_be_relit(&self, pos: glam::IVec3, channel: LightChannel) -> bool {
         for offset in [
             glam::IVec3::X,
             glam::IVec3::NEG_X,
@@ -181,23 +619,29 @@ impl World {
             glam::IVec3::NEG_Z,
         ] {
             let neighbor = pos + offset;
-            let neighbor_light = self.get_light_level(neighbor);
+            let neighbor_light = self.get_light_level(neighbor, channel);
 
             // Sky light goes straight down with no decay
-            if offset == glam::IVec3::Y && neighbor_light == 15 {
+            if channel == LightChannel::Sky && offset == glam::IVec3::Y && neighbor_light == 15 {
                 return true;
             }
 
-            if neighbor_light > self.get_light_level(pos) {
+            if neighbor_light > self.get_light_level(pos, channel) {
                 return true;
             }
         }
         false
     }
 
-    fn run_light_propagation_queue(&mut self, mut queue: VecDeque<glam::IVec3>) {
+    // Returns the chunks whose light changed.
+    fn run_light_propagation_queue(
+        &mut self,
+        mut queue: VecDeque<glam::IVec3>,
+        channel: LightChannel,
+    ) -> HashSet<(i32, i32)> {
+        let mut relit = HashSet::new();
         while let Some(pos) = queue.pop_front() {
-            let current_light = self.get_light_level(pos);
+            let current_light = self.get_light_level(pos, channel);
             let neighbor_light = current_light.saturating_sub(1);
 
             if neighbor_light == 0 && current_light <= 1 {
@@ -214,60 +658,92 @@ impl World {
             ] {
                 let neighbor_pos = pos + offset;
 
-                let potential_light = if offset == glam::IVec3::NEG_Y && current_light == 15 {
+                let potential_light = if channel == LightChannel::Sky
+                    && offset == glam::IVec3::NEG_Y
+                    && current_light == 15
+                {
                     15
                 } else {
                     neighbor_light
                 };
 
-                let neighbor_current_light = self.get_light_level(neighbor_pos);
+                let neighbor_current_light = self.get_light_level(neighbor_pos, channel);
 
                 if self.is_block_transparent(neighbor_pos)
                     && potential_light > neighbor_current_light
                 {
-                    self.set_light_level(neighbor_pos, potential_light);
+                    relit.extend(self.set_light_level(neighbor_pos, channel, potential_light));
                     queue.push_back(neighbor_pos);
                 }
             }
         }
+        relit
     }
 
+    // Relights the block light around a block that changed: light it used to pass on or
+    // give off is taken back, then it either emits its own or lets its neighbours' in.
+    // Returns the chunks whose light changed.
+    fn update_block_light(
+        &mut self,
+        pos: glam::IVec3,
+        block_light_removed: u8,
+    ) -> HashSet<(i32, i32)> {
+        let mut relit = self.propagate_light_removal(pos, block_light_removed, LightChannel::Block);
+
+        let emission = self.get_light_emission(pos, LightChannel::Block);
+        if emission > 0 {
+            relit.extend(self.set_light_level(pos, LightChannel::Block, emission));
+            relit.extend(
+                self.run_light_propagation_queue(VecDeque::from([pos]), LightChannel::Block),
+            );
+        } else if self.is_block_transparent(pos) {
+            relit.extend(self.propagate_light_addition(pos, LightChannel::Block));
+        }
+        relit
+    }
+
+    // Places a block and relights around it. Other loaded chunks whose meshes change (the
+    // ones across a border the block is on, and any whose light changed) are marked dirty;
+    // remeshing the returned chunk is left to the caller.
     pub fn set_block(
         &mut self,
         world_block_pos: glam::IVec3,
         block_type: BlockType,
     ) -> Result<(i32, i32), &'static str> {
-        if world_block_pos.y < 0 || world_block_pos.y >= CHUNK_HEIGHT as i32 {
+        let Some(((chunk_x, chunk_z), (local_x, local_y, local_z))) = World::world_to_chunk_coords(
+            world_block_pos.x as f32,
+            world_block_pos.y as f32,
+            world_block_pos.z as f32,
+        ) else {
             return Err("Y coordinate out of world bounds");
-        }
+        };
 
         let old_block_was_transparent = self.is_block_transparent(world_block_pos);
-        let new_block_is_transparent = Block::new(block_type).is_transparent();
-
-        if old_block_was_transparent == new_block_is_transparent {
-            let ((chunk_x, chunk_z), (local_x, local_y, local_z)) = World::world_to_chunk_coords(
-                world_block_pos.x as f32,
-                world_block_pos.y as f32,
-                world_block_pos.z as f32,
-            );
-            self.get_or_create_chunk(chunk_x, chunk_z)
+        let new_block = Block::new(block_type);
+        let new_block_is_transparent = new_block.is_transparent();
+        let emission_changed = self.get_light_emission(world_block_pos, LightChannel::Block)
+            != new_block.light_emission();
+
+        let sky_light_removed = self.get_light_level(world_block_pos, LightChannel::Sky);
+        let block_light_removed = self.get_light_level(world_block_pos, LightChannel::Block);
+
+        if old_block_was_transparent == new_block_is_transparent && !emission_changed {
+            // Neither channel changes, so the block keeps the light it had.
+            let chunk = self.get_or_create_chunk(chunk_x, chunk_z);
+            chunk
                 .set_block(local_x, local_y, local_z, block_type)
                 .unwrap();
+            chunk.set_sky_light(local_x, local_y, local_z, sky_light_removed);
+            chunk.set_block_light(local_x, local_y, local_z, block_light_removed);
+            self.schedule_fluid_updates_around(world_block_pos);
+            self.mark_neighbours_dirty((chunk_x, chunk_z), world_block_pos, HashSet::new());
             return Ok((chunk_x, chunk_z));
         }
 
-        let ((chunk_x, chunk_z), (local_x, local_y, local_z)) = World::world_to_chunk_coords(
-            world_block_pos.x as f32,
-            world_block_pos.y as f32,
-            world_block_pos.z as f32,
-        );
-
-        let light_level_removed = self.get_light_level(world_block_pos);
-
         let chunk = self.get_or_create_chunk(chunk_x, chunk_z);
         if chunk
             .get_block(local_x, local_y, local_z)
-            .map_or(false, |b| b.block_type == BlockType::Bedrock)
+            .map_or(false, |b| b.block_type == BlockType::BEDROCK)
         {
             return Err("Cannot replace Bedrock");
         }
@@ -276,32 +752,275 @@ impl World {
             .set_block(local_x, local_y, local_z, block_type)
             .unwrap();
 
-        if !old_block_was_transparent && new_block_is_transparent {
-            self.propagate_light_addition(world_block_pos);
+        let mut relit = if old_block_was_transparent != new_block_is_transparent {
+            if new_block_is_transparent {
+                self.propagate_light_addition(world_block_pos, LightChannel::Sky)
+            } else {
+                self.propagate_light_removal(world_block_pos, sky_light_removed, LightChannel::Sky)
+            }
         } else {
-            self.propagate_light_removal(world_block_pos, light_level_removed);
-        }
+            // `Chunk::set_block` cleared both channels; sky light passes as before.
+            self.set_light_level(world_block_pos, LightChannel::Sky, sky_light_removed);
+            HashSet::new()
+        };
+        relit.extend(self.update_block_light(world_block_pos, block_light_removed));
+        self.schedule_fluid_updates_around(world_block_pos);
+        self.mark_neighbours_dirty((chunk_x, chunk_z), world_block_pos, relit);
 
         Ok((chunk_x, chunk_z))
     }
+
+    // Marks the chunks around an edited one that need a new mesh: those meshing a block
+    // around `pos` (only the case on a border) and those in `relit`.
+    fn mark_neighbours_dirty(
+        &mut self,
+        edited: (i32, i32),
+        pos: glam::IVec3,
+        mut relit: HashSet<(i32, i32)>,
+    ) {
+        relit.extend(self.chunks_meshing_around(pos));
+        relit.remove(&edited);
+        self.dirty_chunks.extend(relit);
+    }
+
+    // The loaded chunks whose meshes show a block at or around `pos`: faces, smooth
+    // lighting and fluid slopes all look at the blocks around a face, diagonal ones included.
+    fn chunks_meshing_around(&self, pos: glam::IVec3) -> HashSet<(i32, i32)> {
+        let mut coords = HashSet::new();
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                let (coord, _) =
+                    World::world_to_column_coords((pos.x + dx) as f32, (pos.z + dz) as f32);
+                if self.chunks.contains_key(&coord) {
+                    coords.insert(coord);
+                }
+            }
+        }
+        coords
+    }
+
+    fn schedule_fluid_updates_around(&mut self, pos: glam::IVec3) {
+        self.scheduled_fluid_updates.insert(pos);
+        for offset in [
+            glam::IVec3::X,
+            glam::IVec3::NEG_X,
+            glam::IVec3::Y,
+            glam::IVec3::NEG_Y,
+            glam::IVec3::Z,
+            glam::IVec3::NEG_Z,
+        ] {
+            self.scheduled_fluid_updates.insert(pos + offset);
+        }
+    }
+
+    // Advances fluid flow by one step. Every scheduled block works out its next state from
+    // the world as it was before the step, so the outcome does not depend on the order the
+    // updates are applied in. Blocks that changed schedule themselves and their neighbours
+    // for the next step and mark the chunks that mesh them as dirty.
+    pub fn tick_fluids(&mut self) {
+        let scheduled = std::mem::take(&mut self.scheduled_fluid_updates);
+        let changes: Vec<(glam::IVec3, BlockType, u8)> = scheduled
+            .into_iter()
+            .filter_map(|pos| {
+                let (block_type, fluid_level) = self.next_fluid_state(pos)?;
+                Some((pos, block_type, fluid_level))
+            })
+            .collect();
+        for (pos, block_type, fluid_level) in changes {
+            self.set_fluid_state(pos, block_type, fluid_level);
+            self.schedule_fluid_updates_around(pos);
+        }
+    }
+
+    // What a block turns into on the next fluid step, or None if it stays as it is. Only air
+    // and flowing fluid change: sources and every other block are left alone.
+    fn next_fluid_state(&self, pos: glam::IVec3) -> Option<(BlockType, u8)> {
+        let block = self.block_at(pos)?;
+        let flowing = block.is_fluid() && !block.is_fluid_source();
+        if block.block_type != BlockType::AIR && !flowing {
+            return None;
+        }
+        let next = self.fluid_inflow(pos).unwrap_or((BlockType::AIR, 0));
+        (next != (block.block_type, block.fluid_level)).then_some(next)
+    }
+
+    // The fluid that flows into `pos` from its neighbours, with its level. Fluid falls
+    // straight down at level 1; otherwise it spreads sideways from neighbours that rest on
+    // something they cannot fall into, one level further than the neighbour. The lowest
+    // level wins, and nothing flows on past MAX_FLUID_LEVEL.
+    fn fluid_inflow(&self, pos: glam::IVec3) -> Option<(BlockType, u8)> {
+        if let Some(above) = self.block_at(pos + glam::IVec3::Y)
+            && above.is_fluid()
+        {
+            return Some((above.block_type, 1));
+        }
+        [
+            glam::IVec3::X,
+            glam::IVec3::NEG_X,
+            glam::IVec3::Z,
+            glam::IVec3::NEG_Z,
+        ]
+        .into_iter()
+        .filter_map(|offset| {
+            let neighbour_pos = pos + offset;
+            let neighbour = self.block_at(neighbour_pos)?;
+            if !neighbour.is_fluid() || neighbour.fluid_level >= MAX_FLUID_LEVEL {
+                return None;
+            }
+            let can_fall = self
+                .block_at(neighbour_pos - glam::IVec3::Y)
+                .is_some_and(|below| {
+                    below.block_type == BlockType::AIR || below.block_type == neighbour.block_type
+                });
+            (!can_fall).then_some((neighbour.block_type, neighbour.fluid_level + 1))
+        })
+        .min_by_key(|&(_, fluid_level)| fluid_level)
+    }
+
+    // Writes a fluid (or air, where fluid drained away) without relighting: both let light
+    // through and give off none, so the light already stored stays correct.
+    fn set_fluid_state(&mut self, pos: glam::IVec3, block_type: BlockType, fluid_level: u8) {
+        let Some(((chunk_x, chunk_z), (local_x, local_y, local_z))) =
+            World::world_to_chunk_coords(pos.x as f32, pos.y as f32, pos.z as f32)
+        else {
+            return;
+        };
+        let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_z)) else {
+            return;
+        };
+        let chunk = Arc::make_mut(chunk);
+        let Some(old_block) = chunk.get_block(local_x, local_y, local_z) else {
+            return;
+        };
+        chunk
+            .set_fluid(local_x, local_y, local_z, block_type, fluid_level)
+            .unwrap();
+        chunk.set_sky_light(local_x, local_y, local_z, old_block.sky_light);
+        chunk.set_block_light(local_x, local_y, local_z, old_block.block_light);
+
+        // Fluid surfaces slope towards their neighbours, so every chunk meshing a block
+        // around this one needs a new mesh.
+        let coords = self.chunks_meshing_around(pos);
+        self.dirty_chunks.extend(coords);
+    }
 }
 
-impl Default for World {
-    fn default() -> Self {
-        Self::new()
+// Everything about a new chunk that does not need the rest of the world: base terrain, its
+// own structures and its initial sky light. Returns the chunk and the structure blocks that
+// belong to other chunks. Safe to run on a worker thread.
+pub fn generate_chunk_data(
+    generator: &dyn WorldGenerator,
+    seed: u64,
+    coord: (i32, i32),
+) -> (Chunk, Vec<BlockPlacement>) {
+    let mut chunk = generator.generate_chunk(seed, coord);
+    // Structures are planned on the bare terrain, before neighbours write into it, so the
+    // plan only depends on the seed and not on the order chunks are loaded in.
+    let mut outgoing = generator.place_structures(seed, &chunk);
+    outgoing.retain(|placement| {
+        if placement.chunk_coord() == coord {
+            placement.apply_to(&mut chunk);
+            false
+        } else {
+            true
+        }
+    });
+    chunk.calculate_sky_light();
+    (chunk, outgoing)
+}
+
+// The saved copy of a chunk if there is one, otherwise a newly generated chunk. A saved
+// chunk already contains its own structures, so it has no placements for other chunks.
+// Safe to run on a worker thread.
+pub fn load_or_generate_chunk_data(
+    generator: &dyn WorldGenerator,
+    seed: u64,
+    save: Option<&WorldSave>,
+    coord: (i32, i32),
+) -> (Chunk, Vec<BlockPlacement>) {
+    if let Some(save) = save {
+        match save.load_chunk(coord) {
+            Ok(Some(chunk)) => return (chunk, Vec::new()),
+            Ok(None) => {}
+            Err(e) => eprintln!(
+                "Failed to load chunk {:?}, generating it instead: {}",
+                coord, e
+            ),
+        }
     }
+    generate_chunk_data(generator, seed, coord)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::block::BlockType;
+    use crate::block_registry::registry;
+    use crate::worldgen::flat::{FLAT_SURFACE_Y, FlatWorldGenerator};
     use glam::IVec3;
 
+    // Light tests rely on knowing where the surface is, so they use flat terrain.
+    fn flat_world() -> World {
+        World::with_generator(0, Arc::new(FlatWorldGenerator))
+    }
+
+    // Flat terrain where chunk (0, 0) grows a log just across its +X border.
+    struct BorderLogGenerator;
+
+    const BORDER_LOG_POS: IVec3 = IVec3::new(CHUNK_WIDTH as i32, 20, 3);
+
+    impl WorldGenerator for BorderLogGenerator {
+        fn generate_chunk(&self, seed: u64, chunk_coord: (i32, i32)) -> Chunk {
+            FlatWorldGenerator.generate_chunk(seed, chunk_coord)
+        }
+
+        fn place_structures(&self, _seed: u64, chunk: &Chunk) -> Vec<BlockPlacement> {
+            if chunk.coord != (0, 0) {
+                return Vec::new();
+            }
+            vec![BlockPlacement {
+                position: BORDER_LOG_POS,
+                block_type: BlockType::OAK_LOG,
+                tree_id: None,
+            }]
+        }
+    }
+
+    // Flat terrain where chunk (0, 0) is covered by a stone roof, dark underneath until its
+    // neighbours let light in from the side.
+    struct RoofedChunkGenerator;
+
+    const ROOF_Y: i32 = FLAT_SURFACE_Y + 4;
+
+    impl WorldGenerator for RoofedChunkGenerator {
+        fn generate_chunk(&self, seed: u64, chunk_coord: (i32, i32)) -> Chunk {
+            let mut chunk = FlatWorldGenerator.generate_chunk(seed, chunk_coord);
+            if chunk_coord == (0, 0) {
+                let roof_y = world_y_to_local(ROOF_Y).unwrap();
+                for x in 0..CHUNK_WIDTH {
+                    for z in 0..CHUNK_DEPTH {
+                        chunk.set_block(x, roof_y, z, BlockType::STONE).unwrap();
+                    }
+                }
+            }
+            chunk
+        }
+
+        fn place_structures(&self, _seed: u64, _chunk: &Chunk) -> Vec<BlockPlacement> {
+            Vec::new()
+        }
+    }
+
+    fn block_type_at(world: &World, pos: IVec3) -> Option<BlockType> {
+        world
+            .get_block_at_world(pos.x as f32, pos.y as f32, pos.z as f32)
+            .map(|block| block.block_type)
+    }
+
     #[test]
     fn test_sky_light_update_on_dig() {
         // 1. SETUP: Create a world and generate a chunk.
-        let mut world = World::new();
+        let mut world = flat_world();
         // This generates a chunk at (0,0) with standard terrain and runs the initial
         // sky light calculation. The surface level will be at Y=16.
         world.get_or_create_chunk(0, 0);
@@ -319,7 +1038,7 @@ mod tests {
                 grass_block_pos.z as f32,
             )
             .expect("Grass block should exist.");
-        assert_eq!(grass_block.block_type, BlockType::Grass);
+        assert_eq!(grass_block.block_type, BlockType::GRASS);
         assert_eq!(
             grass_block.sky_light, 0,
             "Surface block should have full sky light."
@@ -340,10 +1059,10 @@ mod tests {
 
         // 3. ACTION: Dig the grass block, replacing it with Air.
         world
-            .set_block(grass_block_pos, BlockType::Air)
+            .set_block(grass_block_pos, BlockType::AIR)
             .expect("Setting block should succeed.");
         world
-            .set_block(block_underneath_pos, BlockType::Air)
+            .set_block(block_underneath_pos, BlockType::AIR)
             .expect("Setting block should succeed.");
 
         // 4. ASSERT FINAL STATE: Check the light level of the newly exposed block.
@@ -365,7 +1084,7 @@ mod tests {
     #[test]
     fn test_sky_light_spreads_horizontally_into_tunnel() {
         // 1. SETUP: Create a world and generate a chunk.
-        let mut world = World::new();
+        let mut world = flat_world();
         world.get_or_create_chunk(0, 0);
 
         // Define the coordinates for the tunnel we will dig.
@@ -388,10 +1107,10 @@ mod tests {
 
         // 3. ACTION: Dig the two blocks to create the tunnel entrance.
         world
-            .set_block(surface_block_pos, BlockType::Air)
+            .set_block(surface_block_pos, BlockType::AIR)
             .expect("Digging surface block should succeed.");
         world
-            .set_block(under_surface_pos, BlockType::Air)
+            .set_block(under_surface_pos, BlockType::AIR)
             .expect("Digging block underneath should succeed.");
 
         //
@@ -409,7 +1128,7 @@ mod tests {
         //
 
         world
-            .set_block(tunnel_end_pos, BlockType::Air)
+            .set_block(tunnel_end_pos, BlockType::AIR)
             .expect("Digging block should succeed.");
 
         // 4. ASSERT FINAL STATE: Check the light level at the end of the one-block tunnel.
@@ -439,7 +1158,7 @@ mod tests {
 
         // 1. SETUP: Create a world and a custom chunk with a predictable surface at y=16.
         // 1. SETUP: Create a world and generate a chunk.
-        let mut world = World::new();
+        let mut world = flat_world();
         world.get_or_create_chunk(0, 0);
 
         // 2. ACTION: Dig out the specific pattern based on the corrected levels.
@@ -451,16 +1170,16 @@ mod tests {
 
         // Dig the vertical shaft at z=5. This will trigger light propagation.
         world
-            .set_block(IVec3::new(5, 16, 5), BlockType::Air)
+            .set_block(IVec3::new(5, 16, 5), BlockType::AIR)
             .unwrap(); // Top grass block
         world
-            .set_block(IVec3::new(5, 15, 5), BlockType::Air)
+            .set_block(IVec3::new(5, 15, 5), BlockType::AIR)
             .unwrap(); // Air at head level in shaft
-        world.set_block(m_pos, BlockType::Air).unwrap(); // Air at foot level in shaft ('m')
+        world.set_block(m_pos, BlockType::AIR).unwrap(); // Air at foot level in shaft ('m')
 
         // Dig the horizontal tunnel for the player
-        world.set_block(h_pos, BlockType::Air).unwrap(); // 'h'
-        world.set_block(f_pos, BlockType::Air).unwrap(); // 'f'
+        world.set_block(h_pos, BlockType::AIR).unwrap(); // 'h'
+        world.set_block(f_pos, BlockType::AIR).unwrap(); // 'f'
 
         // 3. ASSERT: Check the light levels.
         let block_at_m = world
@@ -493,7 +1212,7 @@ mod tests {
         // gg (y=16)    gg (g = grass)
         //
         // Both 'h' and 'f' should be re-lit from the side by column 'a', resulting in light level 14.
-        let mut world = World::new();
+        let mut world = flat_world();
         world.get_or_create_chunk(0, 0);
 
         // Define the column that will be shadowed and the adjacent column that will provide the light.
@@ -503,31 +1222,540 @@ mod tests {
 
         // 2. ASSERT INITIAL STATE: Ensure the player's column is fully lit from the sky.
         assert_eq!(
-            world.get_light_level(head_pos),
+            world.get_light_level(head_pos, LightChannel::Sky),
             15,
             "Head position should initially be fully lit"
         );
         assert_eq!(
-            world.get_light_level(feet_pos),
+            world.get_light_level(feet_pos, LightChannel::Sky),
             15,
             "Feet position should initially be fully lit"
         );
 
         // 3. ACTION: Place a single solid block above the player's head.
-        world.set_block(roof_pos, BlockType::Dirt).unwrap();
+        world.set_block(roof_pos, BlockType::DIRT).unwrap();
 
         // 4. ASSERT FINAL STATE: Check the new, reduced light levels.
         assert_eq!(
-            world.get_light_level(head_pos),
+            world.get_light_level(head_pos, LightChannel::Sky),
             14,
             "Head position should be lit from the side (14)"
         );
         assert_eq!(
-            world.get_light_level(feet_pos),
+            world.get_light_level(feet_pos, LightChannel::Sky),
             14,
             "Feet position should also be lit from the side (14)"
         );
     }
 
-    //
+    #[test]
+    fn test_block_edits_mark_only_the_neighbours_they_reach_dirty() {
+        let mut world = flat_world();
+        for x in -1..=1 {
+            for z in -1..=1 {
+                world.get_or_create_chunk(x, z);
+            }
+        }
+        world.take_dirty_chunks();
+        let y = FLAT_SURFACE_Y + 1;
+        let mut dirty_after_placing = |pos: IVec3, block_type: BlockType| {
+            assert_eq!(world.set_block(pos, block_type), Ok((0, 0)));
+            let mut dirty = world.take_dirty_chunks();
+            dirty.sort_unstable();
+            dirty
+        };
+
+        // The edited chunk is remeshed by the caller.
+        assert!(dirty_after_placing(IVec3::new(8, y, 8), BlockType::STONE).is_empty());
+        assert_eq!(
+            dirty_after_placing(IVec3::new(15, y, 8), BlockType::STONE),
+            vec![(1, 0)]
+        );
+        assert_eq!(
+            dirty_after_placing(IVec3::new(15, y, 15), BlockType::STONE),
+            vec![(0, 1), (1, 0), (1, 1)]
+        );
+        // Torch light crosses the border without the torch being on it.
+        let dirty = dirty_after_placing(IVec3::new(13, y, 8), block_named("torch"));
+        assert!(dirty.contains(&(1, 0)));
+        assert!(!dirty.contains(&(-1, 0)));
+    }
+
+    fn block_named(name: &str) -> BlockType {
+        registry().by_name(name).unwrap()
+    }
+
+    #[test]
+    fn test_torch_light_spreads_and_is_removed_with_it() {
+        let mut world = flat_world();
+        world.get_or_create_chunk(0, 0);
+        let torch_a = IVec3::new(4, FLAT_SURFACE_Y + 4, 8);
+        let torch_b = IVec3::new(12, FLAT_SURFACE_Y + 4, 8);
+        world.set_block(torch_a, block_named("torch")).unwrap();
+        world.set_block(torch_b, block_named("torch")).unwrap();
+
+        assert_eq!(world.get_light_level(torch_a, LightChannel::Block), 14);
+        assert_eq!(
+            world.get_light_level(torch_a + IVec3::new(0, 3, 0), LightChannel::Block),
+            11
+        );
+        // Halfway between the torches both give the same light.
+        assert_eq!(
+            world.get_light_level(IVec3::new(8, torch_a.y, 8), LightChannel::Block),
+            10
+        );
+        // Block light leaves sky light alone.
+        assert_eq!(world.get_light_level(torch_a, LightChannel::Sky), 15);
+
+        world.set_block(torch_a, BlockType::AIR).unwrap();
+        assert_eq!(world.get_light_level(torch_a, LightChannel::Block), 6);
+        assert_eq!(
+            world.get_light_level(torch_a - IVec3::X, LightChannel::Block),
+            5
+        );
+
+        world.set_block(torch_b, BlockType::AIR).unwrap();
+        for x in 0..CHUNK_WIDTH as i32 {
+            assert_eq!(
+                world.get_light_level(IVec3::new(x, torch_a.y, 8), LightChannel::Block),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn test_block_light_goes_around_opaque_blocks() {
+        let mut world = flat_world();
+        world.get_or_create_chunk(0, 0);
+        let glowstone = IVec3::new(8, FLAT_SURFACE_Y + 1, 8);
+        world
+            .set_block(glowstone, block_named("glowstone"))
+            .unwrap();
+        assert_eq!(
+            world.get_light_level(glowstone + IVec3::Y, LightChannel::Block),
+            14
+        );
+
+        // Covering the glowstone forces its light the long way round.
+        world
+            .set_block(glowstone + IVec3::Y, BlockType::STONE)
+            .unwrap();
+        assert_eq!(
+            world.get_light_level(glowstone + IVec3::Y, LightChannel::Block),
+            0
+        );
+        assert_eq!(
+            world.get_light_level(glowstone + IVec3::Y * 2, LightChannel::Block),
+            11
+        );
+        // The emitter itself keeps its light.
+        assert_eq!(world.get_light_level(glowstone, LightChannel::Block), 15);
+
+        world
+            .set_block(glowstone + IVec3::Y, BlockType::AIR)
+            .unwrap();
+        assert_eq!(
+            world.get_light_level(glowstone + IVec3::Y * 2, LightChannel::Block),
+            13
+        );
+    }
+
+    #[test]
+    fn test_sky_light_flows_in_from_a_neighbour_under_a_roof() {
+        let under_roof = |x: i32| IVec3::new(x, ROOF_Y - 2, 5);
+        let mut world = World::with_generator(0, Arc::new(RoofedChunkGenerator));
+        world.get_or_create_chunk(0, 0);
+        assert_eq!(world.get_light_level(under_roof(15), LightChannel::Sky), 0);
+        world.take_dirty_chunks();
+
+        // Loading the open neighbour lights the roofed chunk's border, which needs a remesh.
+        world.get_or_create_chunk(1, 0);
+        assert_eq!(world.get_light_level(under_roof(15), LightChannel::Sky), 14);
+        assert_eq!(world.get_light_level(under_roof(10), LightChannel::Sky), 9);
+        assert_eq!(world.take_dirty_chunks(), vec![(0, 0)]);
+
+        // Loading an open chunk next to another open chunk changes nothing.
+        world.get_or_create_chunk(1, 1);
+        assert_eq!(world.take_dirty_chunks(), Vec::<(i32, i32)>::new());
+
+        // The roofed chunk gets the same light when it is the one loaded last.
+        let mut reversed = World::with_generator(0, Arc::new(RoofedChunkGenerator));
+        reversed.get_or_create_chunk(1, 0);
+        reversed.get_or_create_chunk(0, 0);
+        for x in 0..CHUNK_WIDTH as i32 {
+            assert_eq!(
+                reversed.get_light_level(under_roof(x), LightChannel::Sky),
+                world.get_light_level(under_roof(x), LightChannel::Sky)
+            );
+        }
+    }
+
+    #[test]
+    fn test_structure_write_is_queued_until_neighbour_generates() {
+        let mut world = World::with_generator(0, Arc::new(BorderLogGenerator));
+        world.get_or_create_chunk(0, 0);
+        assert_eq!(block_type_at(&world, BORDER_LOG_POS), None);
+
+        world.get_or_create_chunk(1, 0);
+        assert_eq!(
+            block_type_at(&world, BORDER_LOG_POS),
+            Some(BlockType::OAK_LOG)
+        );
+        // The neighbour was not loaded when the write happened, so it needs no remesh.
+        assert!(world.take_dirty_chunks().is_empty());
+    }
+
+    #[test]
+    fn test_structure_write_into_loaded_neighbour_marks_it_dirty() {
+        let mut world = World::with_generator(0, Arc::new(BorderLogGenerator));
+        world.get_or_create_chunk(1, 0);
+        assert_eq!(block_type_at(&world, BORDER_LOG_POS), Some(BlockType::AIR));
+
+        world.get_or_create_chunk(0, 0);
+        assert_eq!(
+            block_type_at(&world, BORDER_LOG_POS),
+            Some(BlockType::OAK_LOG)
+        );
+        assert_eq!(world.take_dirty_chunks(), vec![(1, 0)]);
+        assert!(world.take_dirty_chunks().is_empty());
+
+        // Sky light below the new log was recomputed.
+        assert_eq!(
+            world.get_light_level(BORDER_LOG_POS - IVec3::Y, LightChannel::Sky),
+            14
+        );
+    }
+
+    #[test]
+    fn test_regenerated_chunk_gets_neighbour_structures_back() {
+        let mut world = World::with_generator(0, Arc::new(BorderLogGenerator));
+        world.get_or_create_chunk(0, 0);
+        world.get_or_create_chunk(1, 0);
+        assert!(world.unload_chunk(1, 0).is_some());
+        assert_eq!(block_type_at(&world, BORDER_LOG_POS), None);
+
+        world.get_or_create_chunk(1, 0);
+        assert_eq!(
+            block_type_at(&world, BORDER_LOG_POS),
+            Some(BlockType::OAK_LOG)
+        );
+    }
+
+    #[test]
+    fn test_saved_chunks_keep_edits_and_pending_structures() {
+        let dir = std::env::temp_dir().join(format!("majn-world-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let save = Arc::new(WorldSave::open(&dir).unwrap());
+        let dug = IVec3::new(2, 15, 2);
+        {
+            let mut world =
+                World::with_generator(0, Arc::new(BorderLogGenerator)).with_save(save.clone());
+            world.get_or_create_chunk(0, 0);
+            world.set_block(dug, BlockType::AIR).unwrap();
+            world.unload_chunk(0, 0);
+
+            world.get_or_create_chunk(0, 0);
+            assert_eq!(block_type_at(&world, dug), Some(BlockType::AIR));
+            // Chunk (1, 0) was never loaded, so its log has to wait in the save.
+            world.save_all().unwrap();
+        }
+
+        let mut world =
+            World::with_generator(0, Arc::new(BorderLogGenerator)).with_save(save.clone());
+        world.get_or_create_chunk(0, 0);
+        world.get_or_create_chunk(1, 0);
+        assert_eq!(block_type_at(&world, dug), Some(BlockType::AIR));
+        assert_eq!(
+            block_type_at(&world, BORDER_LOG_POS),
+            Some(BlockType::OAK_LOG)
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_edits_survive_a_crash_after_structures_were_saved_into_a_chunk() {
+        let dir = std::env::temp_dir().join(format!("majn-placements-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let save = Arc::new(WorldSave::open(&dir).unwrap());
+        {
+            // The log for chunk (1, 0) is written to the placements file on exit.
+            let mut world =
+                World::with_generator(0, Arc::new(BorderLogGenerator)).with_save(save.clone());
+            world.get_or_create_chunk(0, 0);
+            world.save_all().unwrap();
+        }
+        {
+            let mut world =
+                World::with_generator(0, Arc::new(BorderLogGenerator)).with_save(save.clone());
+            world.get_or_create_chunk(1, 0);
+            world.set_block(BORDER_LOG_POS, BlockType::AIR).unwrap();
+            world.unload_chunk(1, 0);
+            // Crash: no `save_all` before the world goes away.
+        }
+
+        let mut world =
+            World::with_generator(0, Arc::new(BorderLogGenerator)).with_save(save.clone());
+        world.get_or_create_chunk(1, 0);
+        assert_eq!(block_type_at(&world, BORDER_LOG_POS), Some(BlockType::AIR));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_structures_for_ungenerated_chunks_survive_a_crash_after_their_source_was_saved() {
+        let dir = std::env::temp_dir().join(format!("majn-sources-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let save = Arc::new(WorldSave::open(&dir).unwrap());
+        {
+            let mut world =
+                World::with_generator(0, Arc::new(BorderLogGenerator)).with_save(save.clone());
+            world.get_or_create_chunk(0, 0);
+            world.unload_chunk(0, 0);
+            // Crash: no `save_all` before the world goes away.
+        }
+
+        // Chunk (0, 0) now loads from disk, without the log it queued for chunk (1, 0).
+        let mut world =
+            World::with_generator(0, Arc::new(BorderLogGenerator)).with_save(save.clone());
+        world.get_or_create_chunk(0, 0);
+        world.get_or_create_chunk(1, 0);
+        assert_eq!(
+            block_type_at(&world, BORDER_LOG_POS),
+            Some(BlockType::OAK_LOG)
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_chunks_match_regardless_of_load_order() {
+        let coords: Vec<(i32, i32)> = (-2..=2)
+            .flat_map(|x| (-2..=2).map(move |z| (x, z)))
+            .collect();
+        let mut forward = World::new(42);
+        for &(x, z) in &coords {
+            forward.get_or_create_chunk(x, z);
+        }
+        let mut backward = World::new(42);
+        for &(x, z) in coords.iter().rev() {
+            backward.get_or_create_chunk(x, z);
+        }
+
+        // Only the inner chunks have all of their neighbours generated.
+        for x in -1..=1 {
+            for z in -1..=1 {
+                let a = forward.get_chunk(x, z).unwrap();
+                let b = backward.get_chunk(x, z).unwrap();
+                for lx in 0..CHUNK_WIDTH {
+                    for y in 0..CHUNK_HEIGHT {
+                        for lz in 0..CHUNK_DEPTH {
+                            assert_eq!(
+                                a.get_block(lx, y, lz).unwrap().block_type,
+                                b.get_block(lx, y, lz).unwrap().block_type,
+                                "Chunk ({}, {}) differs at ({}, {}, {})",
+                                x,
+                                z,
+                                lx,
+                                y,
+                                lz
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_caves_start_dark() {
+        let mut world = World::new(5);
+        let chunk = world.get_or_create_chunk(0, 0);
+        let mut dark_cave_blocks = 0;
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                let mut covered = false;
+                for y in (1..CHUNK_HEIGHT).rev() {
+                    let block = chunk.get_block(x, y, z).unwrap();
+                    if block.is_solid() {
+                        covered = true;
+                    } else if covered && block.block_type == BlockType::AIR && block.sky_light == 0
+                    {
+                        dark_cave_blocks += 1;
+                    }
+                }
+            }
+        }
+        assert!(
+            dark_cave_blocks > 0,
+            "Expected unlit cave air under the surface"
+        );
+    }
+
+    // Ticks fluids until nothing is scheduled any more; returns the number of ticks taken.
+    fn settle_fluids(world: &mut World) -> usize {
+        for ticks in 0..100 {
+            if world.scheduled_fluid_updates.is_empty() {
+                return ticks;
+            }
+            world.tick_fluids();
+        }
+        panic!("Fluids still flowing after 100 ticks");
+    }
+
+    fn fluid_at(world: &World, pos: IVec3) -> Option<u8> {
+        let block = world.block_at(pos).unwrap();
+        block.is_fluid().then_some(block.fluid_level)
+    }
+
+    #[test]
+    fn test_water_spreads_one_level_per_block_then_stops() {
+        let mut world = flat_world();
+        world.get_or_create_chunk(0, 0);
+        let source = IVec3::new(8, FLAT_SURFACE_Y + 1, 8);
+        world.set_block(source, BlockType::WATER).unwrap();
+
+        world.tick_fluids();
+        assert_eq!(fluid_at(&world, source + IVec3::X), Some(1));
+        assert_eq!(fluid_at(&world, source + IVec3::X * 2), None);
+
+        settle_fluids(&mut world);
+        assert_eq!(fluid_at(&world, source), Some(0));
+        for distance in 1..=MAX_FLUID_LEVEL as i32 {
+            assert_eq!(
+                fluid_at(&world, source - IVec3::X * distance),
+                Some(distance as u8)
+            );
+        }
+        assert_eq!(
+            fluid_at(&world, source + IVec3::new(3, 0, 4)),
+            Some(MAX_FLUID_LEVEL)
+        );
+        assert_eq!(
+            fluid_at(&world, source - IVec3::X * (MAX_FLUID_LEVEL as i32 + 1)),
+            None
+        );
+        assert_eq!(fluid_at(&world, source + IVec3::new(4, 0, 4)), None);
+        assert_eq!(fluid_at(&world, source + IVec3::new(1, 1, 0)), None);
+    }
+
+    #[test]
+    fn test_water_falls_before_spreading() {
+        let mut world = flat_world();
+        world.get_or_create_chunk(0, 0);
+        let source = IVec3::new(8, FLAT_SURFACE_Y + 4, 8);
+        world.set_block(source, BlockType::WATER).unwrap();
+        settle_fluids(&mut world);
+
+        // A falling column stays level 1 all the way down, and only spreads on the ground.
+        for y in FLAT_SURFACE_Y + 1..source.y {
+            assert_eq!(fluid_at(&world, IVec3::new(8, y, 8)), Some(1));
+        }
+        for y in FLAT_SURFACE_Y + 2..=source.y {
+            assert_eq!(fluid_at(&world, IVec3::new(9, y, 8)), None);
+        }
+        assert_eq!(
+            fluid_at(&world, IVec3::new(9, FLAT_SURFACE_Y + 1, 8)),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_flowing_water_drains_without_its_source() {
+        let mut world = flat_world();
+        world.get_or_create_chunk(0, 0);
+        let source = IVec3::new(8, FLAT_SURFACE_Y + 1, 8);
+        world.set_block(source, BlockType::WATER).unwrap();
+        settle_fluids(&mut world);
+
+        world.set_block(source, BlockType::AIR).unwrap();
+        settle_fluids(&mut world);
+        for x in 0..CHUNK_WIDTH as i32 {
+            for z in 0..CHUNK_DEPTH as i32 {
+                assert_eq!(fluid_at(&world, IVec3::new(x, source.y, z)), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_water_flows_on_into_chunks_loaded_later() {
+        let mut world = flat_world();
+        world.get_or_create_chunk(0, 0);
+        let source = IVec3::new(14, FLAT_SURFACE_Y + 1, 8);
+        world.set_block(source, BlockType::WATER).unwrap();
+        settle_fluids(&mut world);
+        assert_eq!(fluid_at(&world, source + IVec3::X), Some(1));
+
+        world.get_or_create_chunk(1, 0);
+        settle_fluids(&mut world);
+        for distance in 2..=MAX_FLUID_LEVEL as i32 {
+            assert_eq!(
+                fluid_at(&world, source + IVec3::X * distance),
+                Some(distance as u8)
+            );
+        }
+    }
+
+    #[test]
+    fn test_water_draining_when_saved_drains_after_loading() {
+        let dir = std::env::temp_dir().join(format!("majn-fluid-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let save = Arc::new(WorldSave::open(&dir).unwrap());
+        let source = IVec3::new(8, FLAT_SURFACE_Y + 1, 8);
+        {
+            let mut world = flat_world().with_save(save.clone());
+            world.get_or_create_chunk(0, 0);
+            world.set_block(source, BlockType::WATER).unwrap();
+            settle_fluids(&mut world);
+            world.set_block(source, BlockType::AIR).unwrap();
+            world.tick_fluids();
+            world.save_all().unwrap();
+        }
+
+        let mut world = flat_world().with_save(save.clone());
+        world.get_or_create_chunk(0, 0);
+        assert_eq!(fluid_at(&world, source + IVec3::X * 2), Some(2));
+        settle_fluids(&mut world);
+        for x in 0..CHUNK_WIDTH as i32 {
+            for z in 0..CHUNK_DEPTH as i32 {
+                assert_eq!(fluid_at(&world, IVec3::new(x, source.y, z)), None);
+            }
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_fluid_flow_does_not_depend_on_update_order() {
+        let sources = [
+            IVec3::new(4, FLAT_SURFACE_Y + 1, 6),
+            IVec3::new(9, FLAT_SURFACE_Y + 3, 8),
+            IVec3::new(12, FLAT_SURFACE_Y + 1, 11),
+        ];
+        let mut forward = flat_world();
+        forward.get_or_create_chunk(0, 0);
+        for &source in &sources {
+            forward.set_block(source, BlockType::WATER).unwrap();
+        }
+        let mut backward = flat_world();
+        backward.get_or_create_chunk(0, 0);
+        for &source in sources.iter().rev() {
+            backward.set_block(source, BlockType::WATER).unwrap();
+        }
+
+        for tick in 0..12 {
+            forward.tick_fluids();
+            backward.tick_fluids();
+            for x in 0..CHUNK_WIDTH as i32 {
+                for y in FLAT_SURFACE_Y + 1..FLAT_SURFACE_Y + 4 {
+                    for z in 0..CHUNK_DEPTH as i32 {
+                        let pos = IVec3::new(x, y, z);
+                        assert_eq!(
+                            fluid_at(&forward, pos),
+                            fluid_at(&backward, pos),
+                            "Worlds differ at {} after tick {}",
+                            pos,
+                            tick
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
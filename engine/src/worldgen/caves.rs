@@ -0,0 +1,68 @@
+// engine/src/worldgen/caves.rs
+
+use super::noise_seed;
+use crate::block::BlockType;
+use crate::chunk::{CHUNK_DEPTH, CHUNK_WIDTH, Chunk, local_y_to_world};
+use noise::{NoiseFn, Perlin};
+
+// Tunnels run where two independent 3D noise fields are both close to zero. Each field is
+// zero on a sheet, and two sheets intersect along winding lines, which gives worm-like
+// tunnels rather than the round blobs of a single thresholded field.
+const CAVE_FREQUENCY: f64 = 1.0 / 32.0;
+// Vertical coordinates are stretched so tunnels mostly run sideways.
+const CAVE_VERTICAL_SQUASH: f64 = 2.0;
+// Squared radius around the sheet intersection that gets carved; larger means wider tunnels.
+const CAVE_RADIUS_SQ: f64 = 0.015;
+
+const CAVE_NOISE_LAYER_A: u32 = 1;
+const CAVE_NOISE_LAYER_B: u32 = 2;
+
+pub struct CaveCarver {
+    field_a: Perlin,
+    field_b: Perlin,
+}
+
+impl CaveCarver {
+    pub fn new(seed: u64) -> Self {
+        CaveCarver {
+            field_a: Perlin::new(noise_seed(seed, CAVE_NOISE_LAYER_A)),
+            field_b: Perlin::new(noise_seed(seed, CAVE_NOISE_LAYER_B)),
+        }
+    }
+
+    pub fn is_cave(&self, world_x: i32, world_y: i32, world_z: i32) -> bool {
+        let point = [
+            world_x as f64 * CAVE_FREQUENCY,
+            world_y as f64 * CAVE_FREQUENCY * CAVE_VERTICAL_SQUASH,
+            world_z as f64 * CAVE_FREQUENCY,
+        ];
+        let a = self.field_a.get(point);
+        let b = self.field_b.get(point);
+        a * a + b * b < CAVE_RADIUS_SQ
+    }
+
+    // Hollows out the terrain between bedrock and the surface. `surface_level(x, z)` is the
+    // height of the grass block of a column; tunnels may break through it, which leaves
+    // natural cave entrances.
+    pub fn carve(&self, chunk: &mut Chunk, surface_level: impl Fn(usize, usize) -> usize) {
+        let origin_x = chunk.coord.0 * CHUNK_WIDTH as i32;
+        let origin_z = chunk.coord.1 * CHUNK_DEPTH as i32;
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                for y in 1..=surface_level(x, z) {
+                    if chunk
+                        .get_block(x, y, z)
+                        .is_some_and(|block| block.block_type != BlockType::BEDROCK)
+                        && self.is_cave(
+                            origin_x + x as i32,
+                            local_y_to_world(y),
+                            origin_z + z as i32,
+                        )
+                    {
+                        chunk.set_block(x, y, z, BlockType::AIR).unwrap();
+                    }
+                }
+            }
+        }
+    }
+}
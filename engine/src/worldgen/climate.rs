@@ -0,0 +1,84 @@
+// engine/src/worldgen/climate.rs
+
+use super::noise_seed;
+use crate::biome::Biome;
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+
+// Climate changes far more slowly than the terrain height, so biomes span several chunks.
+const CLIMATE_FREQUENCY: f64 = 1.0 / 384.0;
+const CLIMATE_OCTAVES: usize = 3;
+
+const TEMPERATURE_NOISE_LAYER: u32 = 3;
+const HUMIDITY_NOISE_LAYER: u32 = 4;
+
+// Temperature/humidity thresholds (noise values are roughly in -1..1).
+const HOT_TEMPERATURE: f64 = 0.2;
+const COLD_TEMPERATURE: f64 = -0.2;
+const DRY_HUMIDITY: f64 = 0.0;
+const WET_HUMIDITY: f64 = 0.1;
+
+pub struct ClimateMap {
+    temperature: Fbm<Perlin>,
+    humidity: Fbm<Perlin>,
+}
+
+impl ClimateMap {
+    pub fn new(seed: u64) -> Self {
+        let layer = |layer| {
+            Fbm::<Perlin>::new(noise_seed(seed, layer))
+                .set_octaves(CLIMATE_OCTAVES)
+                .set_frequency(CLIMATE_FREQUENCY)
+        };
+        ClimateMap {
+            temperature: layer(TEMPERATURE_NOISE_LAYER),
+            humidity: layer(HUMIDITY_NOISE_LAYER),
+        }
+    }
+
+    pub fn biome_at(&self, world_x: i32, world_z: i32) -> Biome {
+        let point = [world_x as f64, world_z as f64];
+        let temperature = self.temperature.get(point);
+        let humidity = self.humidity.get(point);
+        classify(temperature, humidity)
+    }
+}
+
+fn classify(temperature: f64, humidity: f64) -> Biome {
+    if temperature > HOT_TEMPERATURE && humidity < DRY_HUMIDITY {
+        Biome::Desert
+    } else if temperature < COLD_TEMPERATURE {
+        Biome::Taiga
+    } else if humidity > WET_HUMIDITY {
+        Biome::Forest
+    } else {
+        Biome::Plains
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_covers_every_biome() {
+        assert_eq!(classify(0.5, -0.5), Biome::Desert);
+        assert_eq!(classify(-0.5, 0.5), Biome::Taiga);
+        assert_eq!(classify(0.0, 0.5), Biome::Forest);
+        assert_eq!(classify(0.0, 0.0), Biome::Plains);
+    }
+
+    #[test]
+    fn test_biomes_vary_across_the_world() {
+        let climate = ClimateMap::new(3);
+        let mut seen = Vec::new();
+        for x in (-4096..4096).step_by(128) {
+            for z in (-4096..4096).step_by(128) {
+                let biome = climate.biome_at(x, z);
+                if !seen.contains(&biome) {
+                    seen.push(biome);
+                }
+            }
+        }
+        assert!(seen.len() >= 3, "Only found biomes {:?}", seen);
+    }
+}
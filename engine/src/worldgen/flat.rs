@@ -0,0 +1,31 @@
+// engine/src/worldgen/flat.rs
+
+use super::WorldGenerator;
+use crate::block::BlockType;
+use crate::chunk::{CHUNK_DEPTH, CHUNK_WIDTH, Chunk, world_y_to_local};
+
+// Flat, tree-less terrain: bedrock at the bottom of the world, dirt up to the grass layer at
+// y=FLAT_SURFACE_Y. Handy for tests that need to know exactly where the surface is.
+pub struct FlatWorldGenerator;
+
+pub const FLAT_SURFACE_Y: i32 = 16;
+
+impl WorldGenerator for FlatWorldGenerator {
+    fn generate_chunk(&self, _seed: u64, chunk_coord: (i32, i32)) -> Chunk {
+        let mut chunk = Chunk::new(chunk_coord.0, chunk_coord.1);
+        let surface_level =
+            world_y_to_local(FLAT_SURFACE_Y).expect("Flat surface must be inside the world");
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                chunk.set_block(x, 0, z, BlockType::BEDROCK).unwrap();
+                for y in 1..surface_level {
+                    chunk.set_block(x, y, z, BlockType::DIRT).unwrap();
+                }
+                chunk
+                    .set_block(x, surface_level, z, BlockType::GRASS)
+                    .unwrap();
+            }
+        }
+        chunk
+    }
+}
@@ -0,0 +1,46 @@
+// engine/src/worldgen/mod.rs
+pub mod caves;
+pub mod climate;
+#[cfg(test)]
+pub mod flat;
+pub mod ores;
+pub mod structures;
+pub mod terrain;
+
+use crate::chunk::Chunk;
+use structures::BlockPlacement;
+
+// Produces the blocks of a single chunk. Implementations must be pure functions of
+// `(seed, chunk_coord)`: the same inputs always yield byte-identical chunks, no matter
+// in which order or how many times chunks are generated.
+pub trait WorldGenerator: Send + Sync {
+    fn generate_chunk(&self, seed: u64, chunk_coord: (i32, i32)) -> Chunk;
+
+    // Structure stage, run once on the freshly generated base terrain of `chunk`. The
+    // returned placements are in world coordinates and may reach into neighbouring
+    // chunks; the `World` applies them there now or once those chunks are generated.
+    fn place_structures(&self, _seed: u64, _chunk: &Chunk) -> Vec<BlockPlacement> {
+        Vec::new()
+    }
+}
+
+// SplitMix64 finalizer; spreads nearby inputs over the whole u64 range.
+fn mix64(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+// Seed for RNG-driven features of one chunk (tree placement, ...). `salt` keeps
+// independent features from sharing a random stream.
+pub fn chunk_seed(world_seed: u64, chunk_coord: (i32, i32), salt: u64) -> u64 {
+    let coord_bits = ((chunk_coord.0 as u32 as u64) << 32) | chunk_coord.1 as u32 as u64;
+    mix64(mix64(world_seed ^ salt) ^ coord_bits)
+}
+
+// Noise crates take 32-bit seeds; fold the world seed down and offset it per layer.
+pub fn noise_seed(world_seed: u64, layer: u32) -> u32 {
+    let mixed = mix64(world_seed.wrapping_add(layer as u64));
+    (mixed ^ (mixed >> 32)) as u32
+}
@@ -0,0 +1,84 @@
+// engine/src/worldgen/ores.rs
+
+use crate::block::BlockType;
+use crate::block_registry::registry;
+use crate::chunk::{CHUNK_DEPTH, CHUNK_WIDTH, Chunk, MAX_Y, MIN_Y, world_y_to_local};
+use rand::Rng;
+
+// One kind of ore and how it is spread through the stone layer.
+#[derive(Debug, Clone)]
+pub struct OreVein {
+    // Registry name of the ore block (see assets/blocks/*.json).
+    pub block_name: &'static str,
+    // Inclusive range of world y that veins are confined to.
+    pub min_y: i32,
+    pub max_y: i32,
+    // Number of blocks a vein tries to convert; blocks that are not stone are skipped.
+    pub vein_size: usize,
+    pub veins_per_chunk: usize,
+}
+
+pub fn default_ore_veins() -> Vec<OreVein> {
+    vec![
+        OreVein {
+            block_name: "coal_ore",
+            min_y: 0,
+            max_y: 80,
+            vein_size: 8,
+            veins_per_chunk: 16,
+        },
+        OreVein {
+            block_name: "iron_ore",
+            min_y: -56,
+            max_y: 40,
+            vein_size: 5,
+            veins_per_chunk: 10,
+        },
+    ]
+}
+
+// Offsets a vein may grow in; a random walk over these gives blobby, connected veins.
+const VEIN_STEPS: [(isize, isize, isize); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+// Replaces stone with ore veins. Veins stay inside the chunk so the result only depends on
+// the chunk's own random stream.
+pub fn place_ore_veins(chunk: &mut Chunk, veins: &[OreVein], rng: &mut impl Rng) {
+    for vein in veins {
+        let ore = registry()
+            .by_name(vein.block_name)
+            .unwrap_or_else(|| panic!("Ore block '{}' is not registered", vein.block_name));
+        let (Some(min_y), Some(max_y)) = (
+            world_y_to_local(vein.min_y.max(MIN_Y)),
+            world_y_to_local(vein.max_y.min(MAX_Y - 1)),
+        ) else {
+            continue;
+        };
+
+        for _ in 0..vein.veins_per_chunk {
+            let mut x = rng.random_range(0..CHUNK_WIDTH);
+            let mut y = rng.random_range(min_y..=max_y);
+            let mut z = rng.random_range(0..CHUNK_DEPTH);
+
+            for _ in 0..vein.vein_size {
+                if chunk
+                    .get_block(x, y, z)
+                    .is_some_and(|block| block.block_type == BlockType::STONE)
+                {
+                    chunk.set_block(x, y, z, ore).unwrap();
+                }
+
+                let (dx, dy, dz) = VEIN_STEPS[rng.random_range(0..VEIN_STEPS.len())];
+                x = x.saturating_add_signed(dx).min(CHUNK_WIDTH - 1);
+                y = y.saturating_add_signed(dy).clamp(min_y, max_y);
+                z = z.saturating_add_signed(dz).min(CHUNK_DEPTH - 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// engine/src/worldgen/structures.rs
+
+use crate::block::BlockType;
+use crate::chunk::{CHUNK_DEPTH, CHUNK_WIDTH, Chunk, MAX_Y, world_y_to_local};
+use glam::IVec3;
+use rand::Rng;
+
+// A single block written by the structure stage, in world block coordinates. It may lie
+// outside the chunk whose generation produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockPlacement {
+    pub position: IVec3,
+    pub block_type: BlockType,
+    pub tree_id: Option<u32>,
+}
+
+impl BlockPlacement {
+    pub fn chunk_coord(&self) -> (i32, i32) {
+        (
+            self.position.x.div_euclid(CHUNK_WIDTH as i32),
+            self.position.z.div_euclid(CHUNK_DEPTH as i32),
+        )
+    }
+
+    // Writes the placement into `chunk` if it belongs there. Structures never overwrite
+    // solid terrain, so canopies wrap around hills and overlapping trees keep their trunks.
+    // Returns true if a block was changed.
+    pub fn apply_to(&self, chunk: &mut Chunk) -> bool {
+        if self.chunk_coord() != chunk.coord {
+            return false;
+        }
+        let Some(local_y) = world_y_to_local(self.position.y) else {
+            return false;
+        };
+        let local_x = self.position.x.rem_euclid(CHUNK_WIDTH as i32) as usize;
+        let local_z = self.position.z.rem_euclid(CHUNK_DEPTH as i32) as usize;
+
+        let occupied = chunk
+            .get_block(local_x, local_y, local_z)
+            .is_none_or(|block| block.is_solid());
+        if occupied {
+            return false;
+        }
+
+        match self.tree_id {
+            Some(tree_id) => {
+                chunk.set_block_with_tree_id(local_x, local_y, local_z, self.block_type, tree_id)
+            }
+            None => chunk.set_block(local_x, local_y, local_z, self.block_type),
+        }
+        .is_ok()
+    }
+}
+
+// Grows an oak whose trunk starts at `base` (world coordinates). The canopy is emitted
+// unclipped; blocks that land in neighbouring chunks are routed there by the `World`.
+pub fn place_oak_tree(
+    placements: &mut Vec<BlockPlacement>,
+    base: IVec3,
+    tree_id: u32,
+    rng: &mut impl Rng,
+) {
+    let trunk_height: i32 = rng.random_range(3..=5);
+    let canopy_radius: i32 = 2;
+    let canopy_base_y_offset: i32 = (trunk_height - 2).max(0);
+    let canopy_max_height_above_base: i32 = 3;
+
+    let tree_top_y = base.y + trunk_height;
+    if tree_top_y + canopy_max_height_above_base >= MAX_Y {
+        return;
+    }
+
+    for i in 0..trunk_height {
+        placements.push(BlockPlacement {
+            position: base + IVec3::new(0, i, 0),
+            block_type: BlockType::OAK_LOG,
+            tree_id: None,
+        });
+    }
+
+    let canopy_center_y = base.y + trunk_height - 1;
+    let y_start_canopy = base.y + canopy_base_y_offset;
+    let y_end_canopy = (base.y + trunk_height + 1).min(MAX_Y - 1);
+
+    for y in y_start_canopy..=y_end_canopy {
+        let y_dist_from_canopy_center = (y - canopy_center_y).abs();
+        // Full radius around the centre layer, one block narrower further away.
+        let current_layer_radius = if y_dist_from_canopy_center <= 1 {
+            canopy_radius
+        } else {
+            (canopy_radius - 1).max(1)
+        };
+
+        for x_offset in -current_layer_radius..=current_layer_radius {
+            for z_offset in -current_layer_radius..=current_layer_radius {
+                // Keep the trunk itself free of leaves below the canopy centre.
+                if x_offset == 0 && z_offset == 0 && y < canopy_center_y {
+                    continue;
+                }
+
+                let dist_sq_horiz = x_offset * x_offset + z_offset * z_offset;
+                if dist_sq_horiz > current_layer_radius * current_layer_radius
+                    && (y_dist_from_canopy_center > 0
+                        || dist_sq_horiz > (canopy_radius + 1) * (canopy_radius + 1))
+                {
+                    continue;
+                }
+
+                let r_core_sq = ((current_layer_radius - 1).max(0) as f32).powi(2);
+                let r_edge_sq = (current_layer_radius as f32).powi(2);
+                let probability = if (dist_sq_horiz as f32) <= r_core_sq {
+                    0.95 // Core part: almost always place
+                } else if (dist_sq_horiz as f32) <= r_edge_sq {
+                    0.6 // Edge part: moderate chance
+                } else {
+                    0.0 // Outside defined canopy for this layer
+                };
+
+                if probability > 0.0 && rng.random_bool(probability) {
+                    placements.push(BlockPlacement {
+                        position: IVec3::new(base.x + x_offset, y, base.z + z_offset),
+                        block_type: BlockType::OAK_LEAVES,
+                        tree_id: Some(tree_id),
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+// engine/src/worldgen/terrain.rs
+
+use super::caves::CaveCarver;
+use super::climate::ClimateMap;
+use super::ores::{OreVein, default_ore_veins, place_ore_veins};
+use super::structures::{BlockPlacement, place_oak_tree};
+use super::{WorldGenerator, chunk_seed, noise_seed};
+use crate::block::BlockType;
+use crate::chunk::{
+    CHUNK_DEPTH, CHUNK_HEIGHT, CHUNK_WIDTH, Chunk, MAX_Y, MIN_Y, local_y_to_world, world_y_to_local,
+};
+use glam::IVec3;
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Average surface height (world y) and how far the heightmap may stray from it (in blocks).
+const BASE_SURFACE_Y: f64 = 64.0;
+const SURFACE_AMPLITUDE: f64 = 24.0;
+// Horizontal scale of the heightmap noise: lower means broader hills.
+const SURFACE_FREQUENCY: f64 = 1.0 / 96.0;
+const SURFACE_OCTAVES: usize = 4;
+// Layers of the biome's subsurface block (dirt, sand) between the surface and the stone.
+const SUBSURFACE_DEPTH: usize = 3;
+
+const HEIGHTMAP_NOISE_LAYER: u32 = 0;
+const TREE_RNG_SALT: u64 = 0x7472_6565; // "tree"
+const ORE_RNG_SALT: u64 = 0x6f72_6573; // "ores"
+const TREE_ID_SALT: u64 = 0x7472_6565_6964; // "treeid"
+
+// Default terrain: a layered-Perlin heightmap with bedrock at the bottom, stone with ore
+// veins and a few layers of biome-specific surface blocks on top, hollowed out by caves and
+// dotted with oak trees. Biomes come from temperature/humidity noise.
+pub struct NoiseWorldGenerator {
+    ore_veins: Vec<OreVein>,
+}
+
+impl Default for NoiseWorldGenerator {
+    fn default() -> Self {
+        Self::with_ore_veins(default_ore_veins())
+    }
+}
+
+impl NoiseWorldGenerator {
+    pub fn with_ore_veins(ore_veins: Vec<OreVein>) -> Self {
+        NoiseWorldGenerator { ore_veins }
+    }
+
+    fn heightmap_noise(seed: u64) -> Fbm<Perlin> {
+        Fbm::<Perlin>::new(noise_seed(seed, HEIGHTMAP_NOISE_LAYER))
+            .set_octaves(SURFACE_OCTAVES)
+            .set_frequency(SURFACE_FREQUENCY)
+    }
+
+    fn heightmap(seed: u64, chunk_coord: (i32, i32)) -> Heightmap {
+        let noise = Self::heightmap_noise(seed);
+        let mut heights = [0; CHUNK_WIDTH * CHUNK_DEPTH];
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                let world_x = chunk_coord.0 as f64 * CHUNK_WIDTH as f64 + x as f64;
+                let world_z = chunk_coord.1 as f64 * CHUNK_DEPTH as f64 + z as f64;
+                let sample = noise.get([world_x, world_z]);
+                let surface = BASE_SURFACE_Y + sample * SURFACE_AMPLITUDE;
+                let surface_y = (surface.round() as i32).clamp(MIN_Y + 1, MAX_Y - 1);
+                heights[x * CHUNK_DEPTH + z] =
+                    world_y_to_local(surface_y).expect("Surface was clamped into the world");
+            }
+        }
+        Heightmap(heights)
+    }
+}
+
+// Surface height of every column in a chunk, as chunk-local y.
+struct Heightmap([usize; CHUNK_WIDTH * CHUNK_DEPTH]);
+
+impl Heightmap {
+    fn get(&self, x: usize, z: usize) -> usize {
+        self.0[x * CHUNK_DEPTH + z]
+    }
+}
+
+impl WorldGenerator for NoiseWorldGenerator {
+    fn generate_chunk(&self, seed: u64, chunk_coord: (i32, i32)) -> Chunk {
+        let mut chunk = Chunk::new(chunk_coord.0, chunk_coord.1);
+        let heights = Self::heightmap(seed, chunk_coord);
+        let climate = ClimateMap::new(seed);
+
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                let biome = climate.biome_at(
+                    chunk_coord.0 * CHUNK_WIDTH as i32 + x as i32,
+                    chunk_coord.1 * CHUNK_DEPTH as i32 + z as i32,
+                );
+                chunk.set_biome(x, z, biome);
+
+                let surface_level = heights.get(x, z);
+                let subsurface_start = surface_level.saturating_sub(SUBSURFACE_DEPTH).max(1);
+                chunk.set_block(x, 0, z, BlockType::BEDROCK).unwrap();
+                for y in 1..subsurface_start {
+                    chunk.set_block(x, y, z, BlockType::STONE).unwrap();
+                }
+                for y in subsurface_start..surface_level {
+                    chunk.set_block(x, y, z, biome.subsurface_block()).unwrap();
+                }
+                chunk
+                    .set_block(x, surface_level, z, biome.surface_block())
+                    .unwrap();
+            }
+        }
+
+        let mut ore_rng = StdRng::seed_from_u64(chunk_seed(seed, chunk_coord, ORE_RNG_SALT));
+        place_ore_veins(&mut chunk, &self.ore_veins, &mut ore_rng);
+
+        // Caves go last so they cut through ore veins as well.
+        CaveCarver::new(seed).carve(&mut chunk, |x, z| heights.get(x, z));
+
+        chunk
+    }
+
+    fn place_structures(&self, seed: u64, chunk: &Chunk) -> Vec<BlockPlacement> {
+        let mut placements = Vec::new();
+        let mut rng = StdRng::seed_from_u64(chunk_seed(seed, chunk.coord, TREE_RNG_SALT));
+        let chunk_origin = IVec3::new(
+            chunk.coord.0 * CHUNK_WIDTH as i32,
+            0,
+            chunk.coord.1 * CHUNK_DEPTH as i32,
+        );
+
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                // Draw for every column so the random stream does not depend on the terrain.
+                let roll: f64 = rng.random();
+                let wants_tree = roll < chunk.get_biome(x, z).tree_chance();
+                let surface_level = (0..CHUNK_HEIGHT)
+                    .rev()
+                    .find(|&y| chunk.get_block(x, y, z).is_some_and(|b| b.is_solid()));
+                let Some(surface_level) = surface_level else {
+                    continue;
+                };
+                let on_grass = chunk
+                    .get_block(x, surface_level, z)
+                    .is_some_and(|block| block.block_type == BlockType::GRASS);
+                if wants_tree && on_grass {
+                    let base = chunk_origin
+                        + IVec3::new(x as i32, local_y_to_world(surface_level) + 1, z as i32);
+                    place_oak_tree(&mut placements, base, tree_id(seed, base), &mut rng);
+                }
+            }
+        }
+
+        placements
+    }
+}
+
+// Tree ids only need to differ between neighbouring trees (leaf shading varies per tree),
+// but deriving them from the position keeps them stable across chunk borders.
+fn tree_id(seed: u64, base: IVec3) -> u32 {
+    (chunk_seed(seed, (base.x, base.z), TREE_ID_SALT) as u32).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_registry::registry;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_same_seed_and_coord_generate_identical_chunks() {
+        let generator = NoiseWorldGenerator::default();
+        for coord in [(0, 0), (-3, 7), (12, -5)] {
+            let first = generator.generate_chunk(1234, coord);
+            let second = generator.generate_chunk(1234, coord);
+            assert!(first == second, "Chunk {:?} differs between runs", coord);
+        }
+    }
+
+    #[test]
+    fn test_different_seeds_generate_different_terrain() {
+        let generator = NoiseWorldGenerator::default();
+        let a = generator.generate_chunk(1, (0, 0));
+        let b = generator.generate_chunk(2, (0, 0));
+        assert!(a != b, "Different seeds should not produce the same chunk");
+    }
+
+    #[test]
+    fn test_trees_reach_across_chunk_borders() {
+        let generator = NoiseWorldGenerator::default();
+        let mut crossing = 0;
+        for chunk_x in -4..4 {
+            for chunk_z in -4..4 {
+                let chunk = generator.generate_chunk(7, (chunk_x, chunk_z));
+                let placements = generator.place_structures(7, &chunk);
+                assert_eq!(placements, generator.place_structures(7, &chunk));
+                crossing += placements
+                    .iter()
+                    .filter(|placement| placement.chunk_coord() != chunk.coord)
+                    .count();
+            }
+        }
+        assert!(crossing > 0, "No tree canopy crossed a chunk border");
+    }
+
+    #[test]
+    fn test_terrain_layering() {
+        let seed = 99;
+        let coord = (4, -2);
+        let chunk = NoiseWorldGenerator::default().generate_chunk(seed, coord);
+        let heights = NoiseWorldGenerator::heightmap(seed, coord);
+        let ores: Vec<BlockType> = default_ore_veins()
+            .iter()
+            .map(|vein| registry().by_name(vein.block_name).unwrap())
+            .collect();
+
+        for x in 0..CHUNK_WIDTH {
+            for z in 0..CHUNK_DEPTH {
+                let surface = heights.get(x, z);
+                let biome = chunk.get_biome(x, z);
+                let subsurface_start = surface.saturating_sub(SUBSURFACE_DEPTH).max(1);
+                assert_eq!(
+                    chunk.get_block(x, 0, z).unwrap().block_type,
+                    BlockType::BEDROCK
+                );
+                for y in 1..CHUNK_HEIGHT {
+                    let block_type = chunk.get_block(x, y, z).unwrap().block_type;
+                    // Caves may leave air anywhere above the bedrock.
+                    if block_type == BlockType::AIR {
+                        continue;
+                    }
+                    if y > surface {
+                        panic!("Unexpected {:?} above the surface", block_type);
+                    } else if y == surface {
+                        assert_eq!(block_type, biome.surface_block());
+                    } else if y >= subsurface_start {
+                        assert_eq!(block_type, biome.subsurface_block());
+                    } else {
+                        assert!(
+                            block_type == BlockType::STONE || ores.contains(&block_type),
+                            "Unexpected {:?} in the stone layer",
+                            block_type
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_caves_and_ores_generate() {
+        let generator = NoiseWorldGenerator::default();
+        let mut counts: HashMap<BlockType, usize> = HashMap::new();
+        let mut cave_blocks = 0;
+        for chunk_x in -2..2 {
+            for chunk_z in -2..2 {
+                let chunk = generator.generate_chunk(5, (chunk_x, chunk_z));
+                let heights = NoiseWorldGenerator::heightmap(5, (chunk_x, chunk_z));
+                for x in 0..CHUNK_WIDTH {
+                    for z in 0..CHUNK_DEPTH {
+                        for y in 1..heights.get(x, z) {
+                            let block_type = chunk.get_block(x, y, z).unwrap().block_type;
+                            if block_type == BlockType::AIR {
+                                cave_blocks += 1;
+                            }
+                            *counts.entry(block_type).or_default() += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert!(cave_blocks > 0, "No caves were carved");
+        for vein in default_ore_veins() {
+            let ore = registry().by_name(vein.block_name).unwrap();
+            assert!(
+                counts.get(&ore).is_some_and(|&count| count > 0),
+                "No {} generated",
+                vein.block_name
+            );
+        }
+    }
+}